@@ -0,0 +1,175 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use num_traits::{cast::AsPrimitive, Zero};
+use std::hint::black_box;
+use zorder::{
+    avx2::{self, DeinterleaveAVX2, InterleaveAVX2},
+    bmi2, Deinterleave, Interleave,
+};
+
+const BATCH_LEN: usize = 4096;
+
+fn bench_avx2(c: &mut Criterion) {
+    let Some(support_token) = avx2::HardwareSupportToken::new() else {
+        panic!("Couldn't run AVX2 benchmarks on this hardware. Try the software based benchmarks.");
+    };
+    let bmi2_token = bmi2::HardwareSupportToken::new();
+
+    let coords: Vec<[u32; 2]> = (0..BATCH_LEN as u32)
+        .map(|i| [i.wrapping_mul(2312347), i.wrapping_mul(76971888)])
+        .collect();
+    let indexes: Vec<u64> = coords
+        .iter()
+        .map(|&coord| zorder::index_of(coord))
+        .collect();
+
+    let mut group = c.benchmark_group("index_of_batch_u32");
+    group.throughput(Throughput::Elements(BATCH_LEN as u64));
+    let mut out = vec![0u64; BATCH_LEN];
+    group.bench_function(BenchmarkId::new("software", BATCH_LEN), |b| {
+        b.iter(|| zorder::index_of_batch(black_box(&coords), &mut out))
+    });
+    if let Some(bmi2_token) = bmi2_token {
+        group.bench_function(BenchmarkId::new("bmi2", BATCH_LEN), |b| {
+            b.iter(|| {
+                for (index, &coord) in out.iter_mut().zip(black_box(&coords)) {
+                    *index = bmi2::index_of(coord, bmi2_token);
+                }
+            })
+        });
+    }
+    group.bench_function(BenchmarkId::new("avx2", BATCH_LEN), |b| {
+        b.iter(|| avx2::index_of_batch(black_box(&coords), &mut out, support_token))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("coord_of_batch_u32");
+    group.throughput(Throughput::Elements(BATCH_LEN as u64));
+    let mut out = vec![[0u32; 2]; BATCH_LEN];
+    group.bench_function(BenchmarkId::new("software", BATCH_LEN), |b| {
+        b.iter(|| zorder::coord_of_batch(black_box(&indexes), &mut out))
+    });
+    if let Some(bmi2_token) = bmi2_token {
+        group.bench_function(BenchmarkId::new("bmi2", BATCH_LEN), |b| {
+            b.iter(|| {
+                for (coord, &index) in out.iter_mut().zip(black_box(&indexes)) {
+                    *coord = bmi2::coord_of(index, bmi2_token);
+                }
+            })
+        });
+    }
+    group.bench_function(BenchmarkId::new("avx2", BATCH_LEN), |b| {
+        b.iter(|| avx2::coord_of_batch(black_box(&indexes), &mut out, support_token))
+    });
+    group.finish();
+
+    let coords: Vec<[u8; 4]> = (0..BATCH_LEN as u32)
+        .map(|i| i.wrapping_mul(2654435761).to_le_bytes())
+        .collect();
+
+    let mut group = c.benchmark_group("index_of_batch_u8_dim4");
+    group.throughput(Throughput::Elements(BATCH_LEN as u64));
+    let mut out = vec![0u32; BATCH_LEN];
+    group.bench_function(BenchmarkId::new("software", BATCH_LEN), |b| {
+        b.iter(|| zorder::index_of_batch(black_box(&coords), &mut out))
+    });
+    if let Some(bmi2_token) = bmi2_token {
+        group.bench_function(BenchmarkId::new("bmi2", BATCH_LEN), |b| {
+            b.iter(|| {
+                for (index, &coord) in out.iter_mut().zip(black_box(&coords)) {
+                    *index = bmi2::index_of(coord, bmi2_token);
+                }
+            })
+        });
+    }
+    group.bench_function(BenchmarkId::new("avx2", BATCH_LEN), |b| {
+        b.iter(|| avx2::index_of_batch(black_box(&coords), &mut out, support_token))
+    });
+    group.finish();
+}
+
+/// Pseudo-random numbers for the batches of every type and dimension.
+fn random() -> impl Iterator<Item = u64> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    core::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    })
+}
+
+fn bench_index_of<I, const N: usize>(c: &mut Criterion, support_token: avx2::HardwareSupportToken)
+where
+    I: InterleaveAVX2<N> + 'static,
+    u64: AsPrimitive<I>,
+{
+    let mut random = random();
+    let coords: Vec<[I; N]> = (0..BATCH_LEN)
+        .map(|_| core::array::from_fn(|_| random.next().unwrap().as_()))
+        .collect();
+    let mut out = vec![<I as Interleave<N>>::Output::zero(); BATCH_LEN];
+
+    let name = format!("index_of_batch_{}_dim{N}", core::any::type_name::<I>());
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(BATCH_LEN as u64));
+    group.bench_function(BenchmarkId::new("software", BATCH_LEN), |b| {
+        b.iter(|| zorder::index_of_batch(black_box(&coords), &mut out))
+    });
+    group.bench_function(BenchmarkId::new("avx2", BATCH_LEN), |b| {
+        b.iter(|| avx2::index_of_batch(black_box(&coords), &mut out, support_token))
+    });
+    group.finish();
+}
+
+fn bench_coord_of<I, const N: usize>(c: &mut Criterion, support_token: avx2::HardwareSupportToken)
+where
+    I: DeinterleaveAVX2<N> + 'static,
+    u64: AsPrimitive<I>,
+{
+    let indexes: Vec<I> = random().take(BATCH_LEN).map(|x| x.as_()).collect();
+    let mut out = vec![[<I as Deinterleave<N>>::Output::zero(); N]; BATCH_LEN];
+
+    let name = format!("coord_of_batch_{}_dim{N}", core::any::type_name::<I>());
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(BATCH_LEN as u64));
+    group.bench_function(BenchmarkId::new("software", BATCH_LEN), |b| {
+        b.iter(|| zorder::coord_of_batch(black_box(&indexes), &mut out))
+    });
+    group.bench_function(BenchmarkId::new("avx2", BATCH_LEN), |b| {
+        b.iter(|| avx2::coord_of_batch(black_box(&indexes), &mut out, support_token))
+    });
+    group.finish();
+}
+
+/// Compares every type and dimension supported by the AVX2 kernels against
+/// the software implementation.
+fn bench_avx2_all(c: &mut Criterion) {
+    let Some(support_token) = avx2::HardwareSupportToken::new() else {
+        panic!("Couldn't run AVX2 benchmarks on this hardware. Try the software based benchmarks.");
+    };
+
+    bench_index_of::<u8, 3>(c, support_token);
+    bench_index_of::<u8, 4>(c, support_token);
+    bench_index_of::<u8, 5>(c, support_token);
+    bench_index_of::<u8, 6>(c, support_token);
+    bench_index_of::<u8, 7>(c, support_token);
+    bench_index_of::<u8, 8>(c, support_token);
+    bench_index_of::<u16, 2>(c, support_token);
+    bench_index_of::<u16, 3>(c, support_token);
+    bench_index_of::<u16, 4>(c, support_token);
+    bench_index_of::<u32, 2>(c, support_token);
+
+    bench_coord_of::<u32, 2>(c, support_token);
+    bench_coord_of::<u32, 3>(c, support_token);
+    bench_coord_of::<u32, 4>(c, support_token);
+    bench_coord_of::<u64, 2>(c, support_token);
+    bench_coord_of::<u64, 3>(c, support_token);
+    bench_coord_of::<u64, 4>(c, support_token);
+    bench_coord_of::<u64, 5>(c, support_token);
+    bench_coord_of::<u64, 6>(c, support_token);
+    bench_coord_of::<u64, 7>(c, support_token);
+    bench_coord_of::<u64, 8>(c, support_token);
+}
+
+criterion_group!(benches, bench_avx2, bench_avx2_all);
+criterion_main!(benches);
@@ -4,6 +4,37 @@ use zorder::bmi2;
 
 fn bench_bmi2(c: &mut Criterion) {
     if let Some(support_token) = bmi2::HardwareSupportToken::new() {
+        c.bench_function("bmi2::array_index_of_u64", |b| {
+            b.iter(|| {
+                bmi2::index_of(
+                    black_box([2765132312347u64, 769718192876348788u64]),
+                    support_token,
+                )
+            })
+        });
+
+        c.bench_function("bmi2::array_coord_of_u64", |b| {
+            b.iter(|| {
+                bmi2::coord_of::<_, 2>(
+                    black_box(2765132312347769718192876348788u128),
+                    support_token,
+                )
+            })
+        });
+
+        c.bench_function("bmi2::array_index_of_u32_dim3", |b| {
+            b.iter(|| {
+                bmi2::index_of(
+                    black_box([2312347u32, 76971888u32, 411237u32]),
+                    support_token,
+                )
+            })
+        });
+
+        c.bench_function("bmi2::array_coord_of_u32_dim3", |b| {
+            b.iter(|| bmi2::coord_of::<_, 3>(black_box(231234776971888411237u128), support_token))
+        });
+
         c.bench_function("bmi2::array_index_of_u32", |b| {
             b.iter(|| bmi2::index_of(black_box([2312347u32, 76971888u32]), support_token))
         });
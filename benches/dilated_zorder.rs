@@ -0,0 +1,103 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use zorder::{
+    coord_of, dilated, index_of,
+    neighbors::{self, Boundary, Direction},
+};
+
+fn bench_dilated(c: &mut Criterion) {
+    let idx = index_of([2312347u32, 76971888u32]);
+    let mut group = c.benchmark_group("increment_u32");
+    group.bench_function("dilated", |b| {
+        b.iter(|| dilated::increment::<_, 2>(black_box(idx), black_box(1)))
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let mut coord = coord_of::<_, 2>(black_box(idx));
+            coord[black_box(1)] = coord[1].wrapping_add(1);
+            index_of(coord)
+        })
+    });
+    group.finish();
+
+    let idx = index_of([23123u16, 1888u16, 11237u16]);
+    let mut group = c.benchmark_group("increment_u16_dim3");
+    group.bench_function("dilated", |b| {
+        b.iter(|| dilated::increment::<_, 3>(black_box(idx), black_box(2)))
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let mut coord = coord_of::<_, 3>(black_box(idx));
+            coord[black_box(2)] = coord[2].wrapping_add(1);
+            index_of(coord)
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("add_u16_dim3");
+    group.bench_function("dilated", |b| {
+        b.iter(|| dilated::add::<_, 3>(black_box(idx), black_box(0), black_box(4711)))
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let mut coord = coord_of::<_, 3>(black_box(idx));
+            coord[black_box(0)] = coord[0].wrapping_add(black_box(4711));
+            index_of(coord)
+        })
+    });
+    group.finish();
+}
+
+fn bench_neighbors(c: &mut Criterion) {
+    let idx = index_of([23123u16, 1888u16, 11237u16]);
+
+    let mut group = c.benchmark_group("neighbor_u16_dim3");
+    group.bench_function("dilated", |b| {
+        b.iter(|| {
+            neighbors::neighbor::<_, 3>(
+                black_box(idx),
+                black_box(1),
+                Direction::Positive,
+                Boundary::Exclude,
+            )
+        })
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let mut coord = coord_of::<_, 3>(black_box(idx));
+            let axis = black_box(1);
+            coord[axis] = coord[axis].checked_add(1)?;
+            Some(index_of(coord))
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("face_neighbors_u16_dim3");
+    group.bench_function("dilated", |b| {
+        b.iter(|| {
+            neighbors::face_neighbors::<_, 3>(black_box(idx), Boundary::Exclude)
+                .flatten()
+                .fold(0, |acc, neighbor| acc ^ neighbor)
+        })
+    });
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            let coord = coord_of::<_, 3>(black_box(idx));
+            let mut acc = 0;
+            for axis in 0..3 {
+                for step in [u16::checked_sub, u16::checked_add] {
+                    if let Some(value) = step(coord[axis], 1) {
+                        let mut neighbor = coord;
+                        neighbor[axis] = value;
+                        acc ^= index_of(neighbor);
+                    }
+                }
+            }
+            acc
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_dilated, bench_neighbors);
+criterion_main!(benches);
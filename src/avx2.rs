@@ -0,0 +1,722 @@
+//! `avx2` module provides batch Z-order curve index and coordinate
+//! calculations using the AVX2 instruction set.
+//!
+//! The kernels convert 8 coordinate sets with 32-bit indexes, or 4
+//! coordinate sets with 64-bit indexes, per instruction sequence by running
+//! the same shift and mask steps as the software implementation in parallel
+//! lanes. 16-bit indexes are not supported, since the software
+//! implementation converts them faster. Coordinates and indexes without a full batch of lanes are converted
+//! with the software implementation, as are all conversions in
+//! [`index_of_batch`](crate::index_of_batch) and
+//! [`coord_of_batch`](crate::coord_of_batch), which serve as the fallback
+//! when AVX2 is not supported.
+//!
+//! Safety of calling [`index_of_batch_unchecked`] and
+//! [`coord_of_batch_unchecked`] can be validated at runtime using
+//! [`has_hardware_support`]. Optionally, you can acquire a
+//! [`HardwareSupportToken`], and then call [`index_of_batch`] and
+//! [`coord_of_batch`] without unsafe.
+//!
+//! # Examples
+//!
+//! ```
+//! # use zorder::avx2;
+//! let coords = [[3u32, 7u32]; 10];
+//! let mut indexes = [0u64; 10];
+//!
+//! if let Some(support_token) = avx2::HardwareSupportToken::new() {
+//!     avx2::index_of_batch(&coords, &mut indexes, support_token);
+//! } else {
+//!     zorder::index_of_batch(&coords, &mut indexes);
+//! }
+//! assert_eq!(indexes, [0b101_111u64; 10]);
+//! ```
+
+use crate::{Deinterleave, Interleave};
+
+/// Returns true if the CPU supports the AVX2 instruction set.
+///
+/// You can use this function to validate that [`index_of_batch_unchecked`]
+/// and [`coord_of_batch_unchecked`] can be safely called.
+pub fn has_hardware_support() -> bool {
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(all(target_arch = "x86_64", feature = "std")))]
+    {
+        false
+    }
+}
+
+/// A token that guarantees that the CPU supports the AVX2 instruction set.
+///
+/// You can freely copy and move this token, but you cannot create an instance
+/// directly. Instead, [`HardwareSupportToken::new`] returns an instance if the
+/// CPU supports the AVX2 instruction set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardwareSupportToken {
+    _private: (),
+}
+
+impl HardwareSupportToken {
+    /// Returns a new instance if the CPU supports the AVX2 instruction set.
+    pub fn new() -> Option<Self> {
+        has_hardware_support().then_some(Self { _private: () })
+    }
+}
+
+/// Interleaves batches of coordinates using AVX2 instruction set.
+pub trait InterleaveAVX2<const N: usize>: Interleave<N> + Copy {
+    /// Calculates the Z-order curve index of each coordinate set in `coords`
+    /// into the same position of `indexes`.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `avx2` x86_64 feature is
+    /// supported by the CPU.
+    unsafe fn interleave_avx2_batch(coords: &[[Self; N]], indexes: &mut [Self::Output]);
+}
+
+/// Deinterleaves batches of indexes using AVX2 instruction set. Inverse of
+/// [`InterleaveAVX2`].
+pub trait DeinterleaveAVX2<const N: usize>: Deinterleave<N> + Copy {
+    /// Calculates the coordinates of each index in `indexes` into the same
+    /// position of `coords`.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `avx2` x86_64 feature is
+    /// supported by the CPU.
+    unsafe fn deinterleave_avx2_batch(indexes: &[Self], coords: &mut [[Self::Output; N]]);
+}
+
+macro_rules! impl_interleave_avx2 {
+    ($kernel:ident => $($dim:expr, $impl_type:ty);*) => {
+        $(
+            impl InterleaveAVX2<$dim> for $impl_type {
+                #[inline]
+                unsafe fn interleave_avx2_batch(
+                    coords: &[[Self; $dim]],
+                    indexes: &mut [<Self as Interleave<$dim>>::Output],
+                ) {
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        kernels::$kernel(coords, indexes)
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    {
+                        let _ = (coords, indexes);
+                        panic!("AVX2 feature is not supported on this architecture")
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_deinterleave_avx2 {
+    ($kernel:ident => $($impl_type:ty => $dim:expr);*) => {
+        $(
+            impl DeinterleaveAVX2<$dim> for $impl_type {
+                #[inline]
+                unsafe fn deinterleave_avx2_batch(
+                    indexes: &[Self],
+                    coords: &mut [[<Self as Deinterleave<$dim>>::Output; $dim]],
+                ) {
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        kernels::$kernel(indexes, coords)
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    {
+                        let _ = (indexes, coords);
+                        panic!("AVX2 feature is not supported on this architecture")
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_interleave_avx2! {
+    interleave_batch_32 =>
+    3, u8;
+    4, u8;
+    2, u16
+}
+
+impl_interleave_avx2! {
+    interleave_batch_64 =>
+    5, u8;
+    6, u8;
+    7, u8;
+    8, u8;
+    3, u16;
+    4, u16;
+    2, u32
+}
+
+impl_deinterleave_avx2! {
+    deinterleave_batch_32 =>
+    u32 => 2;
+    u32 => 3;
+    u32 => 4
+}
+
+impl_deinterleave_avx2! {
+    deinterleave_batch_64 =>
+    u64 => 2;
+    u64 => 3;
+    u64 => 4;
+    u64 => 5;
+    u64 => 6;
+    u64 => 7;
+    u64 => 8
+}
+
+/// Safe wrapper around [`index_of_batch_unchecked`] that requires a
+/// [`HardwareSupportToken`] to guarantee that the AVX2 instruction set is
+/// supported by the CPU.
+///
+/// # Panics
+///
+/// If `coords` and `indexes` have different lengths.
+#[inline]
+pub fn index_of_batch<I, const N: usize>(
+    coords: &[[I; N]],
+    indexes: &mut [<I as Interleave<N>>::Output],
+    _support_token: HardwareSupportToken,
+) where
+    I: InterleaveAVX2<N>,
+{
+    // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+    // the `avx2` instruction set is supported by the CPU and that it is safe
+    // to call `index_of_batch_unchecked`.
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        index_of_batch_unchecked(coords, indexes)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let _ = (coords, indexes);
+        unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+    }
+}
+
+/// Calculates Z-order curve index for each coordinate set in `coords` into
+/// the same position of `indexes`.
+///
+/// This function requires the `avx2` instruction set, but it can be
+/// faster than converting the coordinates one at a time.
+///
+/// # Safety
+///
+/// This function is safe to call only if the `avx2` x86_64 feature is
+/// supported by the CPU. This can be checked at runtime:
+///
+/// ```
+/// if zorder::avx2::has_hardware_support() {
+///     // ...
+/// }
+/// ```
+///
+/// # Panics
+///
+/// If `coords` and `indexes` have different lengths.
+#[inline]
+#[target_feature(enable = "avx2")]
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn index_of_batch_unchecked<I, const N: usize>(
+    coords: &[[I; N]],
+    indexes: &mut [<I as Interleave<N>>::Output],
+) where
+    I: InterleaveAVX2<N>,
+{
+    assert_eq!(coords.len(), indexes.len(), "batch lengths differ");
+    I::interleave_avx2_batch(coords, indexes)
+}
+
+/// Safe wrapper around [`coord_of_batch_unchecked`] that requires a
+/// [`HardwareSupportToken`] to guarantee that the AVX2 instruction set is
+/// supported by the CPU.
+///
+/// # Panics
+///
+/// If `indexes` and `coords` have different lengths.
+#[inline]
+pub fn coord_of_batch<I, const N: usize>(
+    indexes: &[I],
+    coords: &mut [[<I as Deinterleave<N>>::Output; N]],
+    _support_token: HardwareSupportToken,
+) where
+    I: DeinterleaveAVX2<N>,
+{
+    // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+    // the `avx2` instruction set is supported by the CPU and that it is safe
+    // to call `coord_of_batch_unchecked`.
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        coord_of_batch_unchecked(indexes, coords)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let _ = (indexes, coords);
+        unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+    }
+}
+
+/// Calculates the N-dimensional coordinates of each index in `indexes` into
+/// the same position of `coords`.
+///
+/// This function requires the `avx2` instruction set, but it can be
+/// faster than converting the indexes one at a time.
+///
+/// # Safety
+///
+/// This function is safe to call only if the `avx2` x86_64 feature is
+/// supported by the CPU.
+///
+/// # Panics
+///
+/// If `indexes` and `coords` have different lengths.
+#[inline]
+#[target_feature(enable = "avx2")]
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn coord_of_batch_unchecked<I, const N: usize>(
+    indexes: &[I],
+    coords: &mut [[<I as Deinterleave<N>>::Output; N]],
+) where
+    I: DeinterleaveAVX2<N>,
+{
+    assert_eq!(indexes.len(), coords.len(), "batch lengths differ");
+    I::deinterleave_avx2_batch(indexes, coords)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod kernels {
+    use core::arch::x86_64::*;
+
+    use num_traits::cast::AsPrimitive;
+
+    use crate::{
+        deinterleave::DeinterleaveOutput, interleave::InterleaveOutput, mask::BitCount,
+        Deinterleave, Interleave,
+    };
+
+    /// Moves records of `size` bytes between memory and the lanes of a
+    /// vector of `32 / lane` records, where each record is zero-extended to
+    /// a lane of `lane` bytes.
+    ///
+    /// Records which are narrower than a lane are loaded with a masked load
+    /// and spread to the lanes with a dword permutation followed by a byte
+    /// shuffle within each 128-bit half. Stores do the same in reverse, and
+    /// merge the two halves since a dword may hold bytes of both.
+    #[derive(Clone, Copy)]
+    struct Records {
+        size: usize,
+        lane: usize,
+        dwords: __m256i,
+        load_permute: __m256i,
+        load_shuffle: __m256i,
+        store_shuffle: __m256i,
+        store_permute: [__m256i; 2],
+        store_halves: [__m256i; 2],
+    }
+
+    impl Records {
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn new(size: usize, lane: usize) -> Self {
+            let records = 32 / lane;
+            let half_bytes = records / 2 * size;
+            let bytes = records * size;
+            debug_assert!(size <= lane && bytes.is_multiple_of(4));
+
+            // First dword of the records of each half.
+            let first = [0, half_bytes / 4];
+
+            let mut dwords = [0i32; 8];
+            let mut load_permute = [0i32; 8];
+            let mut store_permute = [[0i32; 8]; 2];
+            let mut store_halves = [[0u8; 32]; 2];
+            for i in 0..8 {
+                dwords[i] = if i < bytes / 4 { -1 } else { 0 };
+                load_permute[i] = (first[i / 4] + i % 4).min(7) as i32;
+                for (half, permute) in store_permute.iter_mut().enumerate() {
+                    permute[i] = (4 * half + i.wrapping_sub(first[half]) % 8 % 4) as i32;
+                }
+            }
+
+            let mut load_shuffle = [0x80u8; 32];
+            let mut store_shuffle = [0x80u8; 32];
+            for k in 0..records {
+                let half = k * lane / 16;
+                for b in 0..size {
+                    let reg = k * lane + b;
+                    let mem = k * size + b;
+                    let rel = mem - 4 * first[half];
+                    load_shuffle[reg] = rel as u8;
+                    store_shuffle[16 * half + rel] = (reg % 16) as u8;
+                    store_halves[half][mem] = 0xFF;
+                }
+            }
+
+            Self {
+                size,
+                lane,
+                dwords: _mm256_loadu_si256(dwords.as_ptr().cast()),
+                load_permute: _mm256_loadu_si256(load_permute.as_ptr().cast()),
+                load_shuffle: _mm256_loadu_si256(load_shuffle.as_ptr().cast()),
+                store_shuffle: _mm256_loadu_si256(store_shuffle.as_ptr().cast()),
+                store_permute: store_permute.map(|p| _mm256_loadu_si256(p.as_ptr().cast())),
+                store_halves: store_halves.map(|m| _mm256_loadu_si256(m.as_ptr().cast())),
+            }
+        }
+
+        /// Loads `32 / lane` records starting at `ptr`.
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn load(&self, ptr: *const u8) -> __m256i {
+            if self.size == self.lane {
+                return _mm256_loadu_si256(ptr.cast());
+            }
+
+            let x = _mm256_maskload_epi32(ptr.cast(), self.dwords);
+            let x = _mm256_permutevar8x32_epi32(x, self.load_permute);
+            _mm256_shuffle_epi8(x, self.load_shuffle)
+        }
+
+        /// Stores `32 / lane` records starting at `ptr`.
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn store(&self, ptr: *mut u8, x: __m256i) {
+            if self.size == self.lane {
+                return _mm256_storeu_si256(ptr.cast(), x);
+            }
+
+            let x = _mm256_shuffle_epi8(x, self.store_shuffle);
+            let lo = _mm256_permutevar8x32_epi32(x, self.store_permute[0]);
+            let hi = _mm256_permutevar8x32_epi32(x, self.store_permute[1]);
+            let x = _mm256_or_si256(
+                _mm256_and_si256(lo, self.store_halves[0]),
+                _mm256_and_si256(hi, self.store_halves[1]),
+            );
+            _mm256_maskstore_epi32(ptr.cast(), self.dwords, x);
+        }
+    }
+
+    /// Shift and mask operations on lanes of one width.
+    trait Lanes: Copy + 'static {
+        /// Number of bytes in a lane.
+        const BYTES: usize;
+
+        unsafe fn splat(value: Self) -> __m256i;
+        unsafe fn shl(x: __m256i, count: u32) -> __m256i;
+        unsafe fn shr(x: __m256i, count: u32) -> __m256i;
+    }
+
+    impl Lanes for u32 {
+        const BYTES: usize = 4;
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn splat(value: Self) -> __m256i {
+            _mm256_set1_epi32(value as i32)
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn shl(x: __m256i, count: u32) -> __m256i {
+            _mm256_sll_epi32(x, _mm_cvtsi32_si128(count as i32))
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn shr(x: __m256i, count: u32) -> __m256i {
+            _mm256_srl_epi32(x, _mm_cvtsi32_si128(count as i32))
+        }
+    }
+
+    impl Lanes for u64 {
+        const BYTES: usize = 8;
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn splat(value: Self) -> __m256i {
+            _mm256_set1_epi64x(value as i64)
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn shl(x: __m256i, count: u32) -> __m256i {
+            _mm256_sll_epi64(x, _mm_cvtsi32_si128(count as i32))
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn shr(x: __m256i, count: u32) -> __m256i {
+            _mm256_srl_epi64(x, _mm_cvtsi32_si128(count as i32))
+        }
+    }
+
+    /// Interleaves coordinates into indexes which fit into lanes of type `L`.
+    ///
+    /// Each lane holds one coordinate set as loaded from memory, so the
+    /// coordinates are separated with shifts before interleaving.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn interleave_batch<L, I, const N: usize>(
+        coords: &[[I; N]],
+        indexes: &mut [<I as Interleave<N>>::Output],
+    ) where
+        L: Lanes,
+        I: InterleaveOutput<N> + Interleave<N, Output = <I as InterleaveOutput<N>>::Output>,
+        I: BitCount + Copy,
+        <I as InterleaveOutput<N>>::Output: AsPrimitive<L>,
+        u64: AsPrimitive<L>,
+    {
+        let batch = 32 / L::BYTES;
+        let coord_records = Records::new(core::mem::size_of::<[I; N]>(), L::BYTES);
+        let index_records = Records::new(
+            core::mem::size_of::<<I as Interleave<N>>::Output>(),
+            L::BYTES,
+        );
+        let coord_mask = L::splat(((1u64 << <I as BitCount>::BITS) - 1).as_());
+
+        let mut coord_chunks = coords.chunks_exact(batch);
+        let mut index_chunks = indexes.chunks_exact_mut(batch);
+
+        for (coords, indexes) in (&mut coord_chunks).zip(&mut index_chunks) {
+            let sets = coord_records.load(coords.as_ptr().cast());
+
+            let mut acc = _mm256_setzero_si256();
+            for axis in 0..N {
+                let mut x = L::shr(sets, axis as u32 * <I as BitCount>::BITS);
+                x = _mm256_and_si256(x, coord_mask);
+
+                for &(mask, shift_count) in <I as InterleaveOutput<N>>::STEPS {
+                    let x_shl = L::shl(x, shift_count);
+                    x = _mm256_and_si256(_mm256_or_si256(x, x_shl), L::splat(mask.as_()));
+                }
+
+                acc = _mm256_or_si256(acc, L::shl(x, axis as u32));
+            }
+
+            index_records.store(indexes.as_mut_ptr().cast(), acc);
+        }
+
+        crate::index_of_batch(coord_chunks.remainder(), index_chunks.into_remainder());
+    }
+
+    /// Deinterleaves indexes which fit into lanes of type `L`.
+    ///
+    /// The coordinates of each index are gathered into its lane in the
+    /// layout of a coordinate set in memory before storing. Like
+    /// [`Deinterleave::deinterleave_all`], this transposes the bits with
+    /// [`DeinterleaveOutput::SWAPS`] where possible, and deinterleaves one
+    /// axis at a time otherwise.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn deinterleave_batch<L, I, const N: usize>(
+        indexes: &[I],
+        coords: &mut [[<I as Deinterleave<N>>::Output; N]],
+    ) where
+        L: Lanes,
+        I: DeinterleaveOutput<N> + Deinterleave<N, Output = <I as DeinterleaveOutput<N>>::Output>,
+        I: AsPrimitive<L>,
+        u64: AsPrimitive<L>,
+    {
+        let batch = 32 / L::BYTES;
+        let bits = <<I as Deinterleave<N>>::Output as BitCount>::BITS;
+        let index_records = Records::new(core::mem::size_of::<I>(), L::BYTES);
+        let coord_records = Records::new(
+            core::mem::size_of::<[<I as Deinterleave<N>>::Output; N]>(),
+            L::BYTES,
+        );
+        let lsb_mask = L::splat(<I as DeinterleaveOutput<N>>::LSB_MASK.as_());
+        let used_mask = L::splat((u64::MAX >> (u64::BITS - N as u32 * bits)).as_());
+        let spread = <I as DeinterleaveOutput<N>>::SPREAD;
+        let swaps = <I as DeinterleaveOutput<N>>::SWAPS;
+
+        let mut index_chunks = indexes.chunks_exact(batch);
+        let mut coord_chunks = coords.chunks_exact_mut(batch);
+
+        for (indexes, coords) in (&mut index_chunks).zip(&mut coord_chunks) {
+            let index = index_records.load(indexes.as_ptr().cast());
+
+            let sets = if swaps.is_empty() {
+                let mut sets = _mm256_setzero_si256();
+                for axis in 0..N {
+                    let mut x = _mm256_and_si256(L::shr(index, axis as u32), lsb_mask);
+
+                    for &(mask, shift_count) in <I as DeinterleaveOutput<N>>::STEPS {
+                        let x_shr = L::shr(x, shift_count);
+                        x = _mm256_and_si256(_mm256_or_si256(x, x_shr), L::splat(mask.as_()));
+                    }
+
+                    // Bits above the last coordinate are dropped when storing.
+                    sets = _mm256_or_si256(sets, L::shl(x, axis as u32 * bits));
+                }
+                sets
+            } else {
+                let mut x = index;
+                if !spread.is_empty() {
+                    // Unused bits above the numbers would be spread into them.
+                    x = _mm256_and_si256(x, used_mask);
+                    for &(mask, shift_count) in spread {
+                        let mask = L::splat(mask.as_());
+                        let moved = L::shl(_mm256_and_si256(x, mask), shift_count);
+                        x = _mm256_or_si256(_mm256_andnot_si256(mask, x), moved);
+                    }
+                }
+
+                for &(mask, shift_count) in swaps {
+                    let t = _mm256_xor_si256(L::shr(x, shift_count), x);
+                    let t = _mm256_and_si256(t, L::splat(mask.as_()));
+                    x = _mm256_xor_si256(_mm256_xor_si256(x, t), L::shl(t, shift_count));
+                }
+                x
+            };
+
+            coord_records.store(coords.as_mut_ptr().cast(), sets);
+        }
+
+        crate::coord_of_batch(index_chunks.remainder(), coord_chunks.into_remainder());
+    }
+
+    /// Interleaves coordinates into indexes of at most 32 bits using 32-bit
+    /// lanes.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn interleave_batch_32<I, const N: usize>(
+        coords: &[[I; N]],
+        indexes: &mut [<I as Interleave<N>>::Output],
+    ) where
+        I: InterleaveOutput<N> + Interleave<N, Output = <I as InterleaveOutput<N>>::Output>,
+        I: BitCount + Copy,
+        <I as InterleaveOutput<N>>::Output: AsPrimitive<u32>,
+    {
+        interleave_batch::<u32, I, N>(coords, indexes)
+    }
+
+    /// Interleaves coordinates into 64-bit indexes using 64-bit lanes.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn interleave_batch_64<I, const N: usize>(
+        coords: &[[I; N]],
+        indexes: &mut [<I as Interleave<N>>::Output],
+    ) where
+        I: InterleaveOutput<N> + Interleave<N, Output = <I as InterleaveOutput<N>>::Output>,
+        I: BitCount + Copy,
+        <I as InterleaveOutput<N>>::Output: AsPrimitive<u64>,
+    {
+        interleave_batch::<u64, I, N>(coords, indexes)
+    }
+
+    /// Deinterleaves indexes of at most 32 bits using 32-bit lanes.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn deinterleave_batch_32<I, const N: usize>(
+        indexes: &[I],
+        coords: &mut [[<I as Deinterleave<N>>::Output; N]],
+    ) where
+        I: DeinterleaveOutput<N> + Deinterleave<N, Output = <I as DeinterleaveOutput<N>>::Output>,
+        I: AsPrimitive<u32>,
+    {
+        deinterleave_batch::<u32, I, N>(indexes, coords)
+    }
+
+    /// Deinterleaves 64-bit indexes using 64-bit lanes.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn deinterleave_batch_64<I, const N: usize>(
+        indexes: &[I],
+        coords: &mut [[<I as Deinterleave<N>>::Output; N]],
+    ) where
+        I: DeinterleaveOutput<N> + Deinterleave<N, Output = <I as DeinterleaveOutput<N>>::Output>,
+        I: AsPrimitive<u64>,
+    {
+        deinterleave_batch::<u64, I, N>(indexes, coords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mask::BitCount;
+    use num_traits::{cast::AsPrimitive, PrimInt, Zero};
+
+    /// Number of conversions in each batch, which leaves a remainder for the
+    /// software implementation with both 4 and 8 lanes.
+    const LEN: usize = 61;
+
+    fn check_index_of<I, const N: usize>()
+    where
+        I: InterleaveAVX2<N> + 'static,
+        u64: AsPrimitive<I>,
+        <I as Interleave<N>>::Output: core::fmt::Debug,
+    {
+        let Some(support_token) = HardwareSupportToken::new() else {
+            return;
+        };
+
+        let mut random = crate::util::random();
+        let coords: [[I; N]; LEN] =
+            core::array::from_fn(|_| core::array::from_fn(|_| random.next().unwrap().as_()));
+
+        let mut expected = [<I as Interleave<N>>::Output::zero(); LEN];
+        let mut indexes = [<I as Interleave<N>>::Output::zero(); LEN];
+        crate::index_of_batch(&coords, &mut expected);
+        index_of_batch(&coords, &mut indexes, support_token);
+        assert_eq!(indexes, expected);
+    }
+
+    fn check_coord_of<I, const N: usize>()
+    where
+        I: DeinterleaveAVX2<N> + BitCount + PrimInt + 'static,
+        u64: AsPrimitive<I>,
+        <I as Deinterleave<N>>::Output: core::fmt::Debug,
+    {
+        let Some(support_token) = HardwareSupportToken::new() else {
+            return;
+        };
+
+        let mut random = crate::util::random();
+        let random: [I; LEN] = core::array::from_fn(|_| random.next().unwrap().as_());
+        // Single bits catch a coordinate spilling into the next one.
+        let bits: [I; LEN] = core::array::from_fn(|k| I::one() << (k % I::BITS as usize));
+
+        for indexes in [random, bits] {
+            let mut expected = [[<I as Deinterleave<N>>::Output::zero(); N]; LEN];
+            let mut coords = [[<I as Deinterleave<N>>::Output::zero(); N]; LEN];
+            crate::coord_of_batch(&indexes, &mut expected);
+            coord_of_batch(&indexes, &mut coords, support_token);
+            assert_eq!(coords, expected);
+        }
+    }
+
+    #[test]
+    fn index_of_batch_matches_software() {
+        check_index_of::<u8, 3>();
+        check_index_of::<u8, 4>();
+        check_index_of::<u8, 5>();
+        check_index_of::<u8, 6>();
+        check_index_of::<u8, 7>();
+        check_index_of::<u8, 8>();
+        check_index_of::<u16, 2>();
+        check_index_of::<u16, 3>();
+        check_index_of::<u16, 4>();
+        check_index_of::<u32, 2>();
+    }
+
+    #[test]
+    fn coord_of_batch_matches_software() {
+        check_coord_of::<u32, 2>();
+        check_coord_of::<u32, 3>();
+        check_coord_of::<u32, 4>();
+        check_coord_of::<u64, 2>();
+        check_coord_of::<u64, 3>();
+        check_coord_of::<u64, 4>();
+        check_coord_of::<u64, 5>();
+        check_coord_of::<u64, 6>();
+        check_coord_of::<u64, 7>();
+        check_coord_of::<u64, 8>();
+    }
+}
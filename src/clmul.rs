@@ -0,0 +1,244 @@
+//! `clmul` module provides 2-dimensional Z-order curve index calculations
+//! using the carry-less multiplication (PCLMULQDQ) instruction set.
+//!
+//! Squaring a number with carry-less multiplication spreads its bits to
+//! every other bit, which is exactly the interleaving of
+//! [`Interleave<2>`](crate::Interleave). This is fast on CPUs where the
+//! `bmi2` instructions are microcoded, and the 128-bit output of two `u64`
+//! coordinates is computed with a single multiplication.
+//!
+//! Carry-less multiplication has no cheap inverse, so
+//! [`coord_of`](crate::clmul::coord_of) is a thin wrapper around the software
+//! [`coord_of`](crate::coord_of). It is provided so that both conversions can
+//! be used through the same module.
+//!
+//! Safety of calling [`index_of_unchecked`](crate::clmul::index_of_unchecked)
+//! can be validated at runtime using
+//! [`has_hardware_support`](crate::clmul::has_hardware_support). Optionally,
+//! you can acquire a [`HardwareSupportToken`](crate::clmul::HardwareSupportToken),
+//! and then call [`index_of`](crate::clmul::index_of) without unsafe.
+//!
+//! # Examples
+//!
+//! ```
+//! # use zorder::clmul;
+//! if let Some(support_token) = clmul::HardwareSupportToken::new() {
+//!     let idx = clmul::index_of([3u64, 7u64], support_token);
+//!     assert_eq!(idx, 0b101_111u128);
+//!
+//!     let coord = clmul::coord_of(idx, support_token);
+//!     assert_eq!(coord, [3u64, 7u64]);
+//! }
+//! ```
+
+use crate::{Deinterleave, Interleave};
+
+/// Returns true if the CPU supports the PCLMULQDQ instruction set.
+///
+/// You can use this function to validate that [`index_of_unchecked`] can be
+/// safely called.
+pub fn has_hardware_support() -> bool {
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    {
+        std::is_x86_feature_detected!("pclmulqdq")
+    }
+    #[cfg(not(all(target_arch = "x86_64", feature = "std")))]
+    {
+        false
+    }
+}
+
+/// A token that guarantees that the CPU supports the PCLMULQDQ instruction
+/// set.
+///
+/// You can freely copy and move this token, but you cannot create an instance
+/// directly. Instead, [`HardwareSupportToken::new`] returns an instance if the
+/// CPU supports the PCLMULQDQ instruction set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardwareSupportToken {
+    _private: (),
+}
+
+impl HardwareSupportToken {
+    /// Returns a new instance if the CPU supports the PCLMULQDQ instruction
+    /// set.
+    pub fn new() -> Option<Self> {
+        has_hardware_support().then_some(Self { _private: () })
+    }
+}
+
+/// Interleaves the bits of the given number with a carry-less square.
+pub trait InterleaveCLMUL: Interleave<2> {
+    /// Interleaves the bits of the given number with a carry-less square.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `pclmulqdq` x86_64 feature
+    /// is supported by the CPU.
+    unsafe fn interleave_clmul(self) -> <Self as Interleave<2>>::Output;
+}
+
+macro_rules! impl_interleave_clmul {
+    ($($impl_type:ty),*) => {
+        $(
+            impl InterleaveCLMUL for $impl_type {
+                #[inline]
+                unsafe fn interleave_clmul(self) -> <Self as Interleave<2>>::Output {
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        clmul_square(self as u64) as <Self as Interleave<2>>::Output
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    panic!("PCLMULQDQ feature is not supported on this architecture")
+                }
+            }
+        )*
+    };
+}
+
+impl_interleave_clmul! {
+    u8, u16, u32, u64
+}
+
+/// Squares the given number with carry-less multiplication.
+#[inline]
+#[target_feature(enable = "pclmulqdq")]
+#[cfg(target_arch = "x86_64")]
+unsafe fn clmul_square(x: u64) -> u128 {
+    use core::arch::x86_64::{_mm_clmulepi64_si128, _mm_cvtsi64_si128};
+
+    let x = _mm_cvtsi64_si128(x as i64);
+    core::mem::transmute(_mm_clmulepi64_si128(x, x, 0x00))
+}
+
+/// Safe wrapper around [`index_of_unchecked`] that requires a
+/// [`HardwareSupportToken`] to guarantee that the PCLMULQDQ instruction set
+/// is supported by the CPU.
+#[inline]
+pub fn index_of<I>(
+    array: [I; 2],
+    _support_token: HardwareSupportToken,
+) -> <I as Interleave<2>>::Output
+where
+    I: InterleaveCLMUL,
+{
+    // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+    // the `pclmulqdq` instruction set is supported by the CPU and that it is
+    // safe to call `index_of_unchecked`.
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        index_of_unchecked(array)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let _ = array;
+        unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+    }
+}
+
+/// Calculates 2-dimensional Z-order curve index for given coordinates.
+///
+/// Output type will be the smallest unsigned integer type that can hold both
+/// of the given coordinates.
+///
+/// This function requires the `pclmulqdq` instruction set, but it can be
+/// faster than the software implementation.
+///
+/// # Safety
+///
+/// This function is safe to call only if the `pclmulqdq` x86_64 feature is
+/// supported by the CPU. This can be checked at runtime:
+///
+/// ```
+/// if zorder::clmul::has_hardware_support() {
+///     // ...
+/// }
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::clmul;
+/// if clmul::has_hardware_support() {
+///     // Only works on x86_64 CPUs.
+///     let idx = unsafe { clmul::index_of_unchecked([3u32, 7u32]) };
+///     assert_eq!(idx, 0b101_111u64);
+/// }
+/// ```
+#[inline]
+#[target_feature(enable = "pclmulqdq")]
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn index_of_unchecked<I>(array: [I; 2]) -> <I as Interleave<2>>::Output
+where
+    I: InterleaveCLMUL,
+{
+    crate::util::generic_index_of(array, |x| x.interleave_clmul())
+}
+
+/// Returns the 2-dimensional coordinates of the given Z-order curve index.
+///
+/// Carry-less multiplication has no cheap inverse, so this only forwards to
+/// the software [`coord_of`](crate::coord_of). It has the same signature as
+/// the other hardware accelerated conversions.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::clmul;
+/// if let Some(support_token) = clmul::HardwareSupportToken::new() {
+///     let coord = clmul::coord_of(0b101_111u64, support_token);
+///     assert_eq!(coord, [3u32, 7u32]);
+/// }
+/// ```
+#[inline]
+pub fn coord_of<I>(
+    index: I,
+    _support_token: HardwareSupportToken,
+) -> [<I as Deinterleave<2>>::Output; 2]
+where
+    I: Deinterleave<2> + Copy,
+{
+    crate::coord_of(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_of_matches_software() {
+        let Some(support_token) = HardwareSupportToken::new() else {
+            return;
+        };
+
+        let mut random = crate::util::random();
+        for _ in 0..10_000 {
+            let (x, y) = (random.next().unwrap(), random.next().unwrap());
+
+            assert_eq!(
+                index_of([x as u8, y as u8], support_token),
+                crate::index_of([x as u8, y as u8])
+            );
+            assert_eq!(
+                index_of([x as u16, y as u16], support_token),
+                crate::index_of([x as u16, y as u16])
+            );
+            assert_eq!(
+                index_of([x as u32, y as u32], support_token),
+                crate::index_of([x as u32, y as u32])
+            );
+            assert_eq!(index_of([x, y], support_token), crate::index_of([x, y]));
+        }
+    }
+
+    #[test]
+    fn index_and_back_128() {
+        let Some(support_token) = HardwareSupportToken::new() else {
+            return;
+        };
+
+        let coord = [u64::MAX, 0x8000_0000_0000_0001];
+        let idx = index_of(coord, support_token);
+        assert_eq!(coord_of(idx, support_token), coord);
+    }
+}
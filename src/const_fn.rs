@@ -0,0 +1,153 @@
+//! This module provides `const fn` versions of [`index_of`](crate::index_of)
+//! and [`coord_of`](crate::coord_of), which can be used in `const` contexts
+//! such as static tables and array lengths.
+//!
+//! Trait methods cannot be called in `const fn`, so there is a separate pair
+//! of functions for each combination of coordinate type and dimension. The
+//! functions are named after the coordinates: `index_of_u8x3` interleaves
+//! three `u8` coordinates and `coord_of_u8x3` deinterleaves them back. The
+//! output types are the same as with the trait based functions.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::const_fn::{coord_of_u16x2, index_of_u16x2};
+//!
+//! const IDX: u32 = index_of_u16x2([1, 1]);
+//! const COORD: [u16; 2] = coord_of_u16x2(IDX);
+//!
+//! assert_eq!(IDX, 3);
+//! assert_eq!(COORD, [1, 1]);
+//!
+//! // Index can be used as an array length.
+//! let cells = [0u8; index_of_u16x2([3, 3]) as usize + 1];
+//! assert_eq!(cells.len(), 16);
+//! ```
+
+use crate::{deinterleave::DeinterleaveOutput, interleave::InterleaveOutput};
+
+macro_rules! impl_const_fn {
+    ($($index_of:ident, $coord_of:ident, $dim:expr, $coord_type:ty => $index_type:ty);*) => {
+        $(
+            #[doc = concat!(
+                "Calculates Z-order curve index for ", stringify!($dim), " `",
+                stringify!($coord_type), "` coordinates in a `const` context."
+            )]
+            #[inline]
+            pub const fn $index_of(array: [$coord_type; $dim]) -> $index_type {
+                const STEPS: &[($index_type, u32)] =
+                    <$coord_type as InterleaveOutput<$dim>>::STEPS;
+
+                let mut index = 0;
+                let mut i = 0;
+                while i < $dim {
+                    let mut x = array[i] as $index_type;
+                    let mut step = 0;
+                    while step < STEPS.len() {
+                        let (mask, shift_count) = STEPS[step];
+                        x = (x | (x << shift_count)) & mask;
+                        step += 1;
+                    }
+                    index |= x << i;
+                    i += 1;
+                }
+                index
+            }
+
+            #[doc = concat!(
+                "Returns the ", stringify!($dim), " `", stringify!($coord_type),
+                "` coordinates of the given Z-order curve index in a `const` context."
+            )]
+            #[inline]
+            pub const fn $coord_of(index: $index_type) -> [$coord_type; $dim] {
+                const LSB_MASK: $index_type = <$index_type as DeinterleaveOutput<$dim>>::LSB_MASK;
+                const STEPS: &[($index_type, u32)] =
+                    <$index_type as DeinterleaveOutput<$dim>>::STEPS;
+
+                let mut array = [0; $dim];
+                let mut i = 0;
+                while i < $dim {
+                    let mut x = (index >> i) & LSB_MASK;
+                    let mut step = 0;
+                    while step < STEPS.len() {
+                        let (mask, shift_count) = STEPS[step];
+                        x = (x | (x >> shift_count)) & mask;
+                        step += 1;
+                    }
+                    array[i] = x as $coord_type;
+                    i += 1;
+                }
+                array
+            }
+        )*
+    };
+}
+
+impl_const_fn! {
+    index_of_u8x2, coord_of_u8x2, 2, u8 => u16;
+    index_of_u8x3, coord_of_u8x3, 3, u8 => u32;
+    index_of_u8x4, coord_of_u8x4, 4, u8 => u32;
+    index_of_u8x5, coord_of_u8x5, 5, u8 => u64;
+    index_of_u8x6, coord_of_u8x6, 6, u8 => u64;
+    index_of_u8x7, coord_of_u8x7, 7, u8 => u64;
+    index_of_u8x8, coord_of_u8x8, 8, u8 => u64;
+    index_of_u8x9, coord_of_u8x9, 9, u8 => u128;
+    index_of_u8x10, coord_of_u8x10, 10, u8 => u128;
+    index_of_u8x11, coord_of_u8x11, 11, u8 => u128;
+    index_of_u8x12, coord_of_u8x12, 12, u8 => u128;
+    index_of_u8x13, coord_of_u8x13, 13, u8 => u128;
+    index_of_u8x14, coord_of_u8x14, 14, u8 => u128;
+    index_of_u8x15, coord_of_u8x15, 15, u8 => u128;
+    index_of_u8x16, coord_of_u8x16, 16, u8 => u128;
+    index_of_u16x2, coord_of_u16x2, 2, u16 => u32;
+    index_of_u16x3, coord_of_u16x3, 3, u16 => u64;
+    index_of_u16x4, coord_of_u16x4, 4, u16 => u64;
+    index_of_u16x5, coord_of_u16x5, 5, u16 => u128;
+    index_of_u16x6, coord_of_u16x6, 6, u16 => u128;
+    index_of_u16x7, coord_of_u16x7, 7, u16 => u128;
+    index_of_u16x8, coord_of_u16x8, 8, u16 => u128;
+    index_of_u32x2, coord_of_u32x2, 2, u32 => u64;
+    index_of_u32x3, coord_of_u32x3, 3, u32 => u128;
+    index_of_u32x4, coord_of_u32x4, 4, u32 => u128;
+    index_of_u64x2, coord_of_u64x2, 2, u64 => u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_trait_based() {
+        for i in 0..10_000u64 {
+            let x = i.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            let wide = (x as u128) << 64 | !x as u128;
+
+            assert_eq!(coord_of_u8x2(x as u16), crate::coord_of::<_, 2>(x as u16));
+            assert_eq!(coord_of_u8x3(x as u32), crate::coord_of::<_, 3>(x as u32));
+            assert_eq!(coord_of_u16x2(x as u32), crate::coord_of::<_, 2>(x as u32));
+            assert_eq!(coord_of_u8x7(x), crate::coord_of::<_, 7>(x));
+            assert_eq!(coord_of_u16x3(x), crate::coord_of::<_, 3>(x));
+            assert_eq!(coord_of_u32x2(x), crate::coord_of::<_, 2>(x));
+            assert_eq!(coord_of_u8x13(wide), crate::coord_of::<_, 13>(wide));
+            assert_eq!(coord_of_u16x5(wide), crate::coord_of::<_, 5>(wide));
+            assert_eq!(coord_of_u32x3(wide), crate::coord_of::<_, 3>(wide));
+            assert_eq!(coord_of_u64x2(wide), crate::coord_of::<_, 2>(wide));
+
+            let coord = coord_of_u8x13(wide);
+            assert_eq!(index_of_u8x13(coord), crate::index_of(coord));
+            let coord = coord_of_u16x3(x);
+            assert_eq!(index_of_u16x3(coord), crate::index_of(coord));
+            let coord = coord_of_u64x2(wide);
+            assert_eq!(index_of_u64x2(coord), crate::index_of(coord));
+        }
+    }
+
+    #[test]
+    fn const_evaluation() {
+        const IDX: u64 = index_of_u32x2([3, 7]);
+        const COORD: [u8; 16] = coord_of_u8x16(u128::MAX);
+
+        assert_eq!(IDX, 0b101_111);
+        assert_eq!(COORD, [u8::MAX; 16]);
+    }
+}
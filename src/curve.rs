@@ -0,0 +1,301 @@
+//! Common interface for the space-filling curves of this crate.
+//!
+//! [`SpaceFillingCurve`] maps N-dimensional coordinates to curve indexes and
+//! back. Code written against the trait can switch between curves, and
+//! between software and `bmi2` implementations of the same curve, by
+//! changing a single value. Generic algorithms of this crate, such as
+//! [`BoundingBox::curve_ranges`](crate::query::BoundingBox::curve_ranges),
+//! accept any implementor.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::curve::{Hilbert, SpaceFillingCurve, ZOrder};
+//!
+//! fn sorted<C: SpaceFillingCurve<u8, 2, Index = u16>>(curve: C) -> [[u8; 2]; 4] {
+//!     let mut points = [[1, 1], [0, 0], [0, 1], [1, 0]];
+//!     curve.sort(&mut points);
+//!     points
+//! }
+//!
+//! assert_eq!(sorted(ZOrder), [[0, 0], [1, 0], [0, 1], [1, 1]]);
+//! assert_eq!(sorted(Hilbert), [[0, 0], [1, 0], [1, 1], [0, 1]]);
+//! ```
+
+use num_traits::{PrimInt, Unsigned};
+
+use crate::{
+    bmi2::{DeinterleaveBMI2, HardwareSupportToken, InterleaveBMI2},
+    Deinterleave, Interleave,
+};
+
+/// Mapping between N-dimensional coordinates of type `I` and curve indexes.
+///
+/// Curves divide space recursively into cells. On every level, each cell is
+/// divided into [`RADIX`](Self::RADIX) parts along each axis, and the curve
+/// visits all indexes of one cell before moving on to the next. In other
+/// words, every cell of side `RADIX^l` whose minimum corner coordinates are
+/// multiples of `RADIX^l` covers a contiguous range of `RADIX^(N * l)`
+/// indexes starting at a multiple of `RADIX^(N * l)`. Generic algorithms rely
+/// on this.
+///
+/// The conversions cannot fail, so they must cover every coordinate of type
+/// `I`. The Peano curve covers only part of the coordinate space and is
+/// converted with the fallible functions of [`peano`](crate::peano) instead.
+pub trait SpaceFillingCurve<I, const N: usize> {
+    /// Type of the curve indexes.
+    type Index: Copy;
+
+    /// Number of parts each cell is divided into along each axis.
+    const RADIX: u32 = 2;
+
+    /// Calculates the curve index of the given coordinates.
+    fn index_of(&self, coord: [I; N]) -> Self::Index;
+
+    /// Returns the coordinates of the given curve index.
+    fn coord_of(&self, index: Self::Index) -> [I; N];
+
+    /// Sorts the given points by their curve indexes.
+    #[inline]
+    fn sort(&self, points: &mut [[I; N]])
+    where
+        I: Copy,
+        Self::Index: Ord,
+    {
+        points.sort_unstable_by_key(|&point| self.index_of(point));
+    }
+}
+
+/// Z-order curve using the software implementation of
+/// [`index_of`](crate::index_of) and [`coord_of`](crate::coord_of).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ZOrder;
+
+impl<I, const N: usize> SpaceFillingCurve<I, N> for ZOrder
+where
+    I: Interleave<N>,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I>,
+{
+    type Index = <I as Interleave<N>>::Output;
+
+    #[inline]
+    fn index_of(&self, coord: [I; N]) -> Self::Index {
+        crate::index_of(coord)
+    }
+
+    #[inline]
+    fn coord_of(&self, index: Self::Index) -> [I; N] {
+        crate::coord_of(index)
+    }
+}
+
+/// Z-order curve using the `bmi2` implementation of
+/// [`index_of`](crate::bmi2::index_of) and [`coord_of`](crate::bmi2::coord_of).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZOrderBmi2 {
+    support_token: HardwareSupportToken,
+}
+
+impl ZOrderBmi2 {
+    /// Creates the curve from a token which guarantees that the bmi2
+    /// instruction set is supported by the CPU.
+    #[inline]
+    pub fn new(support_token: HardwareSupportToken) -> Self {
+        Self { support_token }
+    }
+}
+
+impl<I, const N: usize> SpaceFillingCurve<I, N> for ZOrderBmi2
+where
+    I: InterleaveBMI2<N>,
+    <I as Interleave<N>>::Output: DeinterleaveBMI2<N> + Deinterleave<N, Output = I>,
+{
+    type Index = <I as Interleave<N>>::Output;
+
+    #[inline]
+    fn index_of(&self, coord: [I; N]) -> Self::Index {
+        crate::bmi2::index_of(coord, self.support_token)
+    }
+
+    #[inline]
+    fn coord_of(&self, index: Self::Index) -> [I; N] {
+        crate::bmi2::coord_of(index, self.support_token)
+    }
+}
+
+/// Hilbert curve using the software implementation of
+/// [`hilbert::index_of`](crate::hilbert::index_of) and
+/// [`hilbert::coord_of`](crate::hilbert::coord_of).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hilbert;
+
+impl<I, const N: usize> SpaceFillingCurve<I, N> for Hilbert
+where
+    I: Interleave<N> + PrimInt + Unsigned,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I> + Unsigned,
+{
+    type Index = <I as Interleave<N>>::Output;
+
+    #[inline]
+    fn index_of(&self, coord: [I; N]) -> Self::Index {
+        crate::hilbert::index_of(coord)
+    }
+
+    #[inline]
+    fn coord_of(&self, index: Self::Index) -> [I; N] {
+        crate::hilbert::coord_of(index)
+    }
+}
+
+/// Hilbert curve using the `bmi2` implementation of
+/// [`hilbert::bmi2::index_of`](crate::hilbert::bmi2::index_of) and
+/// [`hilbert::bmi2::coord_of`](crate::hilbert::bmi2::coord_of).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HilbertBmi2 {
+    support_token: HardwareSupportToken,
+}
+
+impl HilbertBmi2 {
+    /// Creates the curve from a token which guarantees that the bmi2
+    /// instruction set is supported by the CPU.
+    #[inline]
+    pub fn new(support_token: HardwareSupportToken) -> Self {
+        Self { support_token }
+    }
+}
+
+impl<I, const N: usize> SpaceFillingCurve<I, N> for HilbertBmi2
+where
+    I: InterleaveBMI2<N> + PrimInt + Unsigned,
+    <I as Interleave<N>>::Output: DeinterleaveBMI2<N> + Deinterleave<N, Output = I> + Unsigned,
+{
+    type Index = <I as Interleave<N>>::Output;
+
+    #[inline]
+    fn index_of(&self, coord: [I; N]) -> Self::Index {
+        crate::hilbert::bmi2::index_of(coord, self.support_token)
+    }
+
+    #[inline]
+    fn coord_of(&self, index: Self::Index) -> [I; N] {
+        crate::hilbert::bmi2::coord_of(index, self.support_token)
+    }
+}
+
+/// Gray-coded Z-order curve using the software implementation of
+/// [`gray::index_of`](crate::gray::index_of) and
+/// [`gray::coord_of`](crate::gray::coord_of).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gray;
+
+impl<I, const N: usize> SpaceFillingCurve<I, N> for Gray
+where
+    I: Interleave<N>,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I>,
+{
+    type Index = <I as Interleave<N>>::Output;
+
+    #[inline]
+    fn index_of(&self, coord: [I; N]) -> Self::Index {
+        crate::gray::index_of(coord)
+    }
+
+    #[inline]
+    fn coord_of(&self, index: Self::Index) -> [I; N] {
+        crate::gray::coord_of(index)
+    }
+}
+
+/// Gray-coded Z-order curve using the `bmi2` implementation of
+/// [`gray::bmi2::index_of`](crate::gray::bmi2::index_of) and
+/// [`gray::bmi2::coord_of`](crate::gray::bmi2::coord_of).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrayBmi2 {
+    support_token: HardwareSupportToken,
+}
+
+impl GrayBmi2 {
+    /// Creates the curve from a token which guarantees that the bmi2
+    /// instruction set is supported by the CPU.
+    #[inline]
+    pub fn new(support_token: HardwareSupportToken) -> Self {
+        Self { support_token }
+    }
+}
+
+impl<I, const N: usize> SpaceFillingCurve<I, N> for GrayBmi2
+where
+    I: InterleaveBMI2<N>,
+    <I as Interleave<N>>::Output: DeinterleaveBMI2<N> + Deinterleave<N, Output = I>,
+{
+    type Index = <I as Interleave<N>>::Output;
+
+    #[inline]
+    fn index_of(&self, coord: [I; N]) -> Self::Index {
+        crate::gray::bmi2::index_of(coord, self.support_token)
+    }
+
+    #[inline]
+    fn coord_of(&self, index: Self::Index) -> [I; N] {
+        crate::gray::bmi2::coord_of(index, self.support_token)
+    }
+}
+
+/// Returns `radix^exp - 1` if it fits into `T`.
+#[inline]
+pub(crate) fn checked_pow_minus_one<T: PrimInt>(radix: u32, exp: u32) -> Option<T> {
+    let radix = T::from(radix)?;
+    let digit_max = radix - T::one();
+
+    // `radix^(k + 1) - 1 = (radix^k - 1) * radix + radix - 1`
+    let mut value = T::zero();
+    for _ in 0..exp {
+        value = value.checked_mul(&radix)?.checked_add(&digit_max)?;
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<C: SpaceFillingCurve<u8, 3, Index = u32>>(curve: C, max: u32) {
+        for index in (0..max).step_by(257) {
+            assert_eq!(curve.index_of(curve.coord_of(index)), index);
+        }
+    }
+
+    #[test]
+    fn curves_round_trip() {
+        round_trip(ZOrder, 1 << 24);
+        round_trip(Hilbert, 1 << 24);
+        round_trip(Gray, 1 << 24);
+
+        if let Some(support_token) = HardwareSupportToken::new() {
+            round_trip(ZOrderBmi2::new(support_token), 1 << 24);
+            round_trip(HilbertBmi2::new(support_token), 1 << 24);
+            round_trip(GrayBmi2::new(support_token), 1 << 24);
+        }
+    }
+
+    #[test]
+    fn sort_by_curve() {
+        let mut points = [[3u8, 0u8], [0, 1], [1, 1], [0, 0]];
+
+        ZOrder.sort(&mut points);
+        assert_eq!(points, [[0, 0], [0, 1], [1, 1], [3, 0]]);
+
+        Hilbert.sort(&mut points);
+        assert_eq!(points, [[0, 0], [1, 1], [0, 1], [3, 0]]);
+    }
+
+    #[test]
+    fn pow_minus_one() {
+        assert_eq!(checked_pow_minus_one::<u8>(2, 8), Some(u8::MAX));
+        assert_eq!(checked_pow_minus_one::<u8>(2, 9), None);
+        assert_eq!(checked_pow_minus_one::<u8>(3, 5), Some(242));
+        assert_eq!(checked_pow_minus_one::<u8>(3, 6), None);
+        assert_eq!(checked_pow_minus_one::<u128>(2, 128), Some(u128::MAX));
+        assert_eq!(checked_pow_minus_one::<u32>(3, 0), Some(0));
+    }
+}
@@ -1,18 +1,44 @@
 use num_traits::{cast::AsPrimitive, PrimInt};
 
-use crate::mask::{interleave_mask, interleave_shift, BitCount};
+use crate::{
+    mask::{
+        axis_masks, bit_mask, const_axis_mask, const_interleave_mask, const_steps, const_swaps,
+        BitCount,
+    },
+    sign::{fills_index, sign_bit, Reinterpret},
+};
 
 /// Deinterleave a single number from a set of interleaved numbers. Inverse of
 /// [`Interleave`](crate::interleave::Interleave).
-pub trait Deinterleave<const N: usize>: private::Sealed {
-    /// Smallest unsigned integer type that can hold the deinterleaved bits.
-    type Output;
+pub trait Deinterleave<const N: usize>: private::Sealed + Sized {
+    /// Smallest integer type that can hold the deinterleaved bits. Signed
+    /// indexes are deinterleaved into signed numbers.
+    type Output: BitCount + PrimInt;
+
+    /// Mask of the bits which belong to the number starting from the least
+    /// significant bit. Bits above the `N` numbers are never set.
+    const AXIS_MASK: Self;
 
     /// Deinterleaves a number from a set of interleaved numbers starting from
     /// the given least significant bit (`lsb`) index.
     ///
     /// Dimension `N` determines which bits are extracted to form the output number.
+    /// The `lsb` index of a number is its axis, and it must be less than `N`.
+    /// [`axis_of`](crate::axis_of) checks the axis before deinterleaving.
     fn deinterleave(self, lsb: usize) -> Self::Output;
+
+    /// Deinterleaves all `N` numbers from a set of interleaved numbers at
+    /// once. Element `i` of the output is the number starting from the `i`th
+    /// least significant bit.
+    ///
+    /// Implementations share the work between the numbers where possible.
+    #[inline]
+    fn deinterleave_all(self) -> [Self::Output; N]
+    where
+        Self: Copy,
+    {
+        core::array::from_fn(|i| self.deinterleave(i))
+    }
 }
 
 impl<T, const N: usize> Deinterleave<N> for T
@@ -23,27 +49,135 @@ where
 {
     type Output = <Self as DeinterleaveOutput<N>>::Output;
 
+    const AXIS_MASK: Self = <Self as DeinterleaveOutput<N>>::AXIS_MASK;
+
     #[inline]
     fn deinterleave(self, lsb: usize) -> <Self as Deinterleave<N>>::Output {
-        let mut x = (self >> lsb) & interleave_mask(N as u32, 1);
-
-        for i in 0..<Self::Output as BitCount>::BITS_ILOG2 {
-            let mask = interleave_mask(N as u32, 1 << (i + 1));
-            let shift_count = interleave_shift(N as u32, i);
+        let mut x = (self >> lsb) & <Self as DeinterleaveOutput<N>>::LSB_MASK;
 
+        for &(mask, shift_count) in <Self as DeinterleaveOutput<N>>::STEPS {
             x = (x | x.unsigned_shr(shift_count)) & mask;
         }
 
         x.as_()
     }
+
+    /// Deinterleaves all numbers at once. With a power of two dimension of
+    /// at least 4, or any dimension of at least 3 with 8-bit numbers, the
+    /// bits are transposed in place with [`DeinterleaveOutput::SWAPS`] so
+    /// that each number ends up in consecutive bits. Other dimensions than
+    /// powers of two are first padded with [`DeinterleaveOutput::SPREAD`].
+    /// Otherwise, the numbers are deinterleaved one at a time.
+    #[inline]
+    fn deinterleave_all(self) -> [Self::Output; N] {
+        let swaps = <Self as DeinterleaveOutput<N>>::SWAPS;
+        if !swaps.is_empty() {
+            let bits = <<Self as DeinterleaveOutput<N>>::Output as BitCount>::BITS;
+            let spread = <Self as DeinterleaveOutput<N>>::SPREAD;
+
+            let mut x = self;
+            if !spread.is_empty() {
+                // Unused bits above the numbers would be spread into them.
+                x = x & bit_mask::<Self>(N as u32 * bits);
+                for &(mask, shift_count) in spread {
+                    x = (x & !mask) | (x & mask).unsigned_shl(shift_count);
+                }
+            }
+
+            for &(mask, shift_count) in swaps {
+                let t = (x.unsigned_shr(shift_count) ^ x) & mask;
+                x = x ^ t ^ t.unsigned_shl(shift_count);
+            }
+
+            return core::array::from_fn(|i| x.unsigned_shr(i as u32 * bits).as_());
+        }
+
+        core::array::from_fn(|i| self.deinterleave(i))
+    }
+}
+
+/// Implements [`Deinterleave`] for signed indexes. Inverse of the signed
+/// [`Interleave`](crate::interleave::Interleave) implementations.
+macro_rules! impl_deinterleave_signed {
+    ($($impl_type:ty => $unsigned:ty);*) => {
+        $(
+            impl<const N: usize> Deinterleave<N> for $impl_type
+            where
+                $unsigned: Deinterleave<N>,
+                <$unsigned as Deinterleave<N>>::Output: Reinterpret,
+            {
+                type Output = <<$unsigned as Deinterleave<N>>::Output as Reinterpret>::Other;
+
+                const AXIS_MASK: Self = <$unsigned as Deinterleave<N>>::AXIS_MASK as $impl_type;
+
+                #[inline]
+                fn deinterleave(self, lsb: usize) -> Self::Output {
+                    let coord = <$unsigned as Deinterleave<N>>::deinterleave(unsigned_index::<Self, N>(self), lsb);
+                    (coord ^ sign_bit()).reinterpret()
+                }
+
+                #[inline]
+                fn deinterleave_all(self) -> [Self::Output; N] {
+                    <$unsigned as Deinterleave<N>>::deinterleave_all(unsigned_index::<Self, N>(self))
+                        .map(|coord| (coord ^ sign_bit()).reinterpret())
+                }
+            }
+        )*
+    };
+}
+
+impl_deinterleave_signed! {
+    i16 => u16;
+    i32 => u32;
+    i64 => u64;
+    i128 => u128
+}
+
+/// Reverts the sign bit flip of signed indexes, which is done when the sign
+/// bit of the last number is the most significant bit of the index.
+#[inline]
+fn unsigned_index<I, const N: usize>(index: I) -> <I as Reinterpret>::Other
+where
+    I: Deinterleave<N> + Reinterpret,
+{
+    if fills_index::<<I as Deinterleave<N>>::Output, I>(N) {
+        (index ^ sign_bit()).reinterpret()
+    } else {
+        index.reinterpret()
+    }
 }
 
 /// Used to determine the minimum width output type which fits
 /// all dimensions `N` stored in the input type.
 ///
 /// Inverse conversion of [`InterleaveOutput`](crate::interleave::InterleaveOutput).
-pub trait DeinterleaveOutput<const N: usize>: private::Sealed {
+///
+/// Masks and shift amounts of each deinterleaving step are computed at
+/// compile time for every type and dimension combination.
+pub trait DeinterleaveOutput<const N: usize>: private::Sealed + Sized + 'static {
     type Output: BitCount + PrimInt;
+
+    /// Mask of the bits which belong to the number starting from the least
+    /// significant bit.
+    const LSB_MASK: Self;
+
+    /// Same as [`LSB_MASK`](Self::LSB_MASK), but without the bits above the
+    /// `N` numbers.
+    const AXIS_MASK: Self;
+
+    /// Mask and shift amount of each deinterleaving step in the order they
+    /// are applied.
+    const STEPS: &'static [(Self, u32)];
+
+    /// Mask and shift amount of each move which pads the groups of `N` bits
+    /// to the next power of two before [`SWAPS`](Self::SWAPS). Empty if the
+    /// groups are not padded.
+    const SPREAD: &'static [(Self, u32)];
+
+    /// Mask and shift amount of each delta swap which deinterleaves all
+    /// numbers at once. Empty if the numbers are deinterleaved one at a
+    /// time.
+    const SWAPS: &'static [(Self, u32)];
 }
 
 macro_rules! impl_deinterleave_output {
@@ -51,6 +185,18 @@ macro_rules! impl_deinterleave_output {
         $(
             impl DeinterleaveOutput<$dim> for $impl_type {
                 type Output = $out_type;
+
+                const LSB_MASK: Self = const_interleave_mask($dim, 1, <$impl_type>::BITS) as $impl_type;
+
+                const AXIS_MASK: Self =
+                    const_axis_mask($dim, <$out_type>::BITS, <$impl_type>::BITS) as $impl_type;
+
+                const STEPS: &'static [(Self, u32)] =
+                    &const_steps!(deinterleave_steps, $dim, $out_type, $impl_type);
+
+                const SPREAD: &'static [(Self, u32)] = &const_swaps!(spread_moves, $dim, $impl_type);
+
+                const SWAPS: &'static [(Self, u32)] = &const_swaps!(transpose_swaps, $dim, $impl_type);
             }
         )*
     };
@@ -96,6 +242,21 @@ pub trait DeinterleaveBMI2<const N: usize>: Deinterleave<N> {
     /// This function is safe to call only if the `bmi2` x86_64 feature is
     /// supported by the CPU.
     unsafe fn deinterleave_bmi2(self, lsb: usize) -> <Self as Deinterleave<N>>::Output;
+
+    /// Deinterleaves all `N` numbers from a set of interleaved numbers at
+    /// once using BMI2 instruction set.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU.
+    #[inline]
+    unsafe fn deinterleave_all_bmi2(self) -> [<Self as Deinterleave<N>>::Output; N]
+    where
+        Self: Copy,
+    {
+        core::array::from_fn(|i| self.deinterleave_bmi2(i))
+    }
 }
 
 macro_rules! impl_deinterleave_bmi2_32 {
@@ -106,7 +267,8 @@ macro_rules! impl_deinterleave_bmi2_32 {
                 unsafe fn deinterleave_bmi2(self, lsb: usize) -> <Self as Deinterleave<$dim>>::Output {
                     #[cfg(target_arch = "x86_64")]
                     {
-                        let mask = interleave_mask::<u32>($dim, 1) << lsb;
+                        const MASK: u32 = const_interleave_mask($dim, 1, u32::BITS) as u32;
+                        let mask = MASK << lsb;
                         core::arch::x86_64::_pext_u32(self.as_(), mask).as_()
                     }
                     #[cfg(not(target_arch = "x86_64"))]
@@ -115,6 +277,18 @@ macro_rules! impl_deinterleave_bmi2_32 {
                         panic!("BMI2 feature is not supported on this architecture")
                     }
                 }
+
+                #[inline]
+                unsafe fn deinterleave_all_bmi2(self) -> [<Self as Deinterleave<$dim>>::Output; $dim] {
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        const MASKS: [u128; $dim] = axis_masks::<$dim>(u32::BITS);
+                        let index = self.as_();
+                        core::array::from_fn(|i| core::arch::x86_64::_pext_u32(index, MASKS[i] as u32).as_())
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    panic!("BMI2 feature is not supported on this architecture")
+                }
             }
         )*
     };
@@ -128,7 +302,8 @@ macro_rules! impl_deinterleave_bmi2_64 {
                 unsafe fn deinterleave_bmi2(self, lsb: usize) -> <Self as Deinterleave<$dim>>::Output {
                     #[cfg(target_arch = "x86_64")]
                     {
-                        let mask = interleave_mask::<u64>($dim, 1) << lsb;
+                        const MASK: u64 = const_interleave_mask($dim, 1, u64::BITS) as u64;
+                        let mask = MASK << lsb;
                         core::arch::x86_64::_pext_u64(self.as_(), mask).as_()
                     }
                     #[cfg(not(target_arch = "x86_64"))]
@@ -137,6 +312,113 @@ macro_rules! impl_deinterleave_bmi2_64 {
                         panic!("BMI2 feature is not supported on this architecture")
                     }
                 }
+
+                /// Transposing the bits of 8-bit numbers in software is faster than
+                /// extracting each of them separately, unless the numbers need to
+                /// be padded first.
+                #[inline]
+                unsafe fn deinterleave_all_bmi2(self) -> [<Self as Deinterleave<$dim>>::Output; $dim] {
+                    let out_bits = <<Self as Deinterleave<$dim>>::Output as BitCount>::BITS;
+                    let transposed = !<Self as DeinterleaveOutput<$dim>>::SWAPS.is_empty()
+                        && <Self as DeinterleaveOutput<$dim>>::SPREAD.is_empty();
+                    if out_bits == u8::BITS && transposed {
+                        return <Self as Deinterleave<$dim>>::deinterleave_all(self);
+                    }
+
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        const MASKS: [u128; $dim] = axis_masks::<$dim>(u64::BITS);
+                        let index = self.as_();
+                        core::array::from_fn(|i| core::arch::x86_64::_pext_u64(index, MASKS[i] as u64).as_())
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    panic!("BMI2 feature is not supported on this architecture")
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [`DeinterleaveBMI2`] for `u128` indexes by extracting the bits
+/// of the low and high halves of the index separately and joining them.
+macro_rules! impl_deinterleave_bmi2_128 {
+    ($($impl_type:ty => $dim:expr);*) => {
+        $(
+            impl DeinterleaveBMI2<$dim> for $impl_type {
+                #[inline]
+                unsafe fn deinterleave_bmi2(self, lsb: usize) -> <Self as Deinterleave<$dim>>::Output {
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        use core::arch::x86_64::_pext_u64;
+
+                        const MASK: u128 = const_interleave_mask($dim, 1, u128::BITS);
+                        let mask = MASK << lsb;
+                        let lo_mask = mask as u64;
+                        let hi_mask = (mask >> 64) as u64;
+
+                        let lo = _pext_u64(self as u64, lo_mask);
+                        let hi = _pext_u64((self >> 64) as u64, hi_mask);
+                        (hi << lo_mask.count_ones() | lo).as_()
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    {
+                        let _ = lsb;
+                        panic!("BMI2 feature is not supported on this architecture")
+                    }
+                }
+
+                /// Splits the index into halves once for all numbers. Transposing
+                /// the bits of 8-bit numbers in software is faster than extracting
+                /// each of them separately, unless the numbers need to be padded
+                /// first.
+                #[inline]
+                unsafe fn deinterleave_all_bmi2(self) -> [<Self as Deinterleave<$dim>>::Output; $dim] {
+                    let out_bits = <<Self as Deinterleave<$dim>>::Output as BitCount>::BITS;
+                    let transposed = !<Self as DeinterleaveOutput<$dim>>::SWAPS.is_empty()
+                        && <Self as DeinterleaveOutput<$dim>>::SPREAD.is_empty();
+                    if out_bits == u8::BITS && transposed {
+                        return <Self as Deinterleave<$dim>>::deinterleave_all(self);
+                    }
+
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        use core::arch::x86_64::_pext_u64;
+
+                        const MASKS: [u128; $dim] = axis_masks::<$dim>(u128::BITS);
+                        let (lo, hi) = (self as u64, (self >> 64) as u64);
+                        core::array::from_fn(|i| {
+                            let lo_mask = MASKS[i] as u64;
+                            let hi_mask = (MASKS[i] >> 64) as u64;
+                            (_pext_u64(hi, hi_mask) << lo_mask.count_ones() | _pext_u64(lo, lo_mask)).as_()
+                        })
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    panic!("BMI2 feature is not supported on this architecture")
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_deinterleave_bmi2_signed {
+    ($($impl_type:ty => $unsigned:ty);*) => {
+        $(
+            impl<const N: usize> DeinterleaveBMI2<N> for $impl_type
+            where
+                $unsigned: DeinterleaveBMI2<N>,
+                <$unsigned as Deinterleave<N>>::Output: Reinterpret,
+            {
+                #[inline]
+                unsafe fn deinterleave_bmi2(self, lsb: usize) -> <Self as Deinterleave<N>>::Output {
+                    let coord = <$unsigned as DeinterleaveBMI2<N>>::deinterleave_bmi2(unsigned_index::<Self, N>(self), lsb);
+                    (coord ^ sign_bit()).reinterpret()
+                }
+
+                #[inline]
+                unsafe fn deinterleave_all_bmi2(self) -> [<Self as Deinterleave<N>>::Output; N] {
+                    <$unsigned as DeinterleaveBMI2<N>>::deinterleave_all_bmi2(unsigned_index::<Self, N>(self))
+                        .map(|coord| (coord ^ sign_bit()).reinterpret())
+                }
             }
         )*
     };
@@ -159,6 +441,31 @@ impl_deinterleave_bmi2_64! {
     u64 => 8
 }
 
+impl_deinterleave_bmi2_128! {
+    u128 => 2;
+    u128 => 3;
+    u128 => 4;
+    u128 => 5;
+    u128 => 6;
+    u128 => 7;
+    u128 => 8;
+    u128 => 9;
+    u128 => 10;
+    u128 => 11;
+    u128 => 12;
+    u128 => 13;
+    u128 => 14;
+    u128 => 15;
+    u128 => 16
+}
+
+impl_deinterleave_bmi2_signed! {
+    i16 => u16;
+    i32 => u32;
+    i64 => u64;
+    i128 => u128
+}
+
 mod private {
     pub trait Sealed {}
 
@@ -166,6 +473,10 @@ mod private {
     impl Sealed for u32 {}
     impl Sealed for u64 {}
     impl Sealed for u128 {}
+    impl Sealed for i16 {}
+    impl Sealed for i32 {}
+    impl Sealed for i64 {}
+    impl Sealed for i128 {}
 }
 
 #[cfg(test)]
@@ -216,6 +527,73 @@ mod tests {
         assert_eq!(y, 0);
     }
 
+    #[test]
+    fn deinterleave_dim2_i8() {
+        let x = <i16 as Deinterleave<2>>::deinterleave(0i16, 0);
+        let y = <i16 as Deinterleave<2>>::deinterleave(0i16, 1);
+
+        assert_eq!(x, i8::MIN);
+        assert_eq!(y, 0);
+    }
+
+    #[test]
+    fn axis_mask_first_number() {
+        let mask = <u32 as Deinterleave<3>>::AXIS_MASK;
+        assert_eq!(mask, 0x0024_9249);
+        assert_eq!(
+            <u32 as Deinterleave<3>>::deinterleave_all(mask),
+            [u8::MAX, 0, 0]
+        );
+
+        let mask = <u128 as Deinterleave<7>>::AXIS_MASK;
+        assert_eq!(mask.leading_zeros(), u128::BITS - 7 * u16::BITS + 6);
+        assert_eq!(<u128 as Deinterleave<7>>::deinterleave(mask, 0), u16::MAX);
+
+        let mask = <i64 as Deinterleave<3>>::AXIS_MASK;
+        assert_eq!(mask, <u64 as Deinterleave<3>>::AXIS_MASK as i64);
+    }
+
+    fn check_all<I, const N: usize>(indexes: impl Iterator<Item = I>)
+    where
+        I: DeinterleaveBMI2<N> + Copy,
+        <I as Deinterleave<N>>::Output: PartialEq + core::fmt::Debug,
+    {
+        let has_bmi2 = crate::bmi2::has_hardware_support();
+        for index in indexes {
+            let each: [_; N] = core::array::from_fn(|i| index.deinterleave(i));
+            assert_eq!(index.deinterleave_all(), each);
+            if has_bmi2 {
+                // SAFETY: Support for `bmi2` was checked above.
+                assert_eq!(unsafe { index.deinterleave_all_bmi2() }, each);
+            }
+        }
+    }
+
+    #[test]
+    fn deinterleave_all_matches_each() {
+        let mut random = crate::util::random();
+        let wide = |x: u64| (x as u128) << 64 | x.rotate_left(32) as u128;
+
+        check_all::<u16, 2>(random.by_ref().take(100).map(|x| x as u16));
+        check_all::<u32, 3>(random.by_ref().take(100).map(|x| x as u32));
+        check_all::<u64, 3>(random.by_ref().take(100));
+        check_all::<u64, 5>(random.by_ref().take(100));
+        check_all::<u64, 6>(random.by_ref().take(100));
+        check_all::<u32, 4>(random.by_ref().take(100).map(|x| x as u32));
+        check_all::<u64, 4>(random.by_ref().take(100));
+        check_all::<u64, 7>(random.by_ref().take(100));
+        check_all::<u64, 8>(random.by_ref().take(100));
+        check_all::<i64, 8>(random.by_ref().take(100).map(|x| x as i64));
+        check_all::<u128, 2>(random.by_ref().take(100).map(wide));
+        check_all::<u128, 3>(random.by_ref().take(100).map(wide));
+        check_all::<u128, 4>(random.by_ref().take(100).map(wide));
+        check_all::<u128, 7>(random.by_ref().take(100).map(wide));
+        check_all::<u128, 8>(random.by_ref().take(100).map(wide));
+        check_all::<u128, 13>(random.by_ref().take(100).map(wide));
+        check_all::<u128, 16>(random.by_ref().take(100).map(wide));
+        check_all::<i128, 16>(random.by_ref().take(100).map(|x| wide(x) as i128));
+    }
+
     #[test]
     fn odd_large_dimension_interleave() {
         let idx =
@@ -0,0 +1,491 @@
+//! Arithmetic on single axes of Z-order curve indexes.
+//!
+//! Moving around a grid in Z-order usually means decoding an index, changing
+//! one coordinate and encoding the index again. The functions in this module
+//! instead operate directly on the interleaved bits of one axis, leaving
+//! the other axes untouched. [`Dilated`] provides the same operations for a
+//! single coordinate stored in interleaved form.
+//!
+//! All arithmetic wraps around at the bounds of the coordinate type. Every
+//! function which takes an `axis` panics if it is not less than `N`.
+//!
+//! Signed indexes store each coordinate with its sign bit flipped, so only
+//! [`increment`] and [`decrement`] accept them. The other functions require
+//! unsigned indexes and coordinates.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::{dilated, index_of};
+//!
+//! let idx = index_of([3u16, 7u16]);
+//!
+//! let idx = dilated::increment::<_, 2>(idx, 0);
+//! assert_eq!(idx, index_of([4u16, 7u16]));
+//!
+//! let idx = dilated::sub::<_, 2>(idx, 1, 5);
+//! assert_eq!(idx, index_of([4u16, 2u16]));
+//! ```
+
+use core::{
+    cmp::Ordering,
+    ops::{Add, Sub},
+};
+
+use num_traits::{PrimInt, Unsigned, WrappingAdd, WrappingSub};
+
+use crate::{mask::BitCount, Deinterleave, Interleave};
+
+/// Returns the mask of the bits belonging to `axis` of an index.
+#[inline]
+pub(crate) fn mask<I, const N: usize>(axis: usize) -> I
+where
+    I: Deinterleave<N> + BitCount + PrimInt,
+{
+    assert!(axis < N, "axis {axis} out of range for {N} dimensions");
+    <I as Deinterleave<N>>::AXIS_MASK << axis
+}
+
+/// Adds the bits of `a` and `b` selected by `mask`, carrying over the bits
+/// outside of the mask.
+#[inline]
+fn dilated_add<I: PrimInt + WrappingAdd>(a: I, b: I, mask: I) -> I {
+    (a | !mask).wrapping_add(&(b & mask)) & mask
+}
+
+/// Subtracts the bits of `b` selected by `mask` from the bits of `a`
+/// selected by `mask`, borrowing over the bits outside of the mask.
+#[inline]
+fn dilated_sub<I: PrimInt + WrappingSub>(a: I, b: I, mask: I) -> I {
+    (a & mask).wrapping_sub(&(b & mask)) & mask
+}
+
+/// Adds one to the coordinate of `axis` of the given index.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{dilated, index_of};
+/// let idx = dilated::increment::<_, 3>(index_of([1u8, 2u8, 255u8]), 2);
+/// assert_eq!(idx, index_of([1u8, 2u8, 0u8]));
+/// ```
+#[inline]
+pub fn increment<I, const N: usize>(index: I, axis: usize) -> I
+where
+    I: Deinterleave<N> + BitCount + PrimInt + WrappingAdd,
+{
+    let mask = mask::<I, N>(axis);
+    ((index | !mask).wrapping_add(&I::one()) & mask) | (index & !mask)
+}
+
+/// Subtracts one from the coordinate of `axis` of the given index.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{dilated, index_of};
+/// let idx = dilated::decrement::<_, 2>(index_of([0u32, 9u32]), 0);
+/// assert_eq!(idx, index_of([u32::MAX, 9u32]));
+/// ```
+#[inline]
+pub fn decrement<I, const N: usize>(index: I, axis: usize) -> I
+where
+    I: Deinterleave<N> + BitCount + PrimInt + WrappingSub,
+{
+    let mask = mask::<I, N>(axis);
+    ((index & mask).wrapping_sub(&I::one()) & mask) | (index & !mask)
+}
+
+/// Adds `value` to the coordinate of `axis` of the given index.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{dilated, index_of};
+/// let idx = dilated::add::<_, 2>(index_of([5u8, 9u8]), 1, 20);
+/// assert_eq!(idx, index_of([5u8, 29u8]));
+/// ```
+#[inline]
+pub fn add<I, const N: usize>(index: I, axis: usize, value: <I as Deinterleave<N>>::Output) -> I
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd,
+    <I as Deinterleave<N>>::Output: Interleave<N, Output = I>,
+{
+    let mask = mask::<I, N>(axis);
+    dilated_add(index, value.interleave() << axis, mask) | (index & !mask)
+}
+
+/// Subtracts `value` from the coordinate of `axis` of the given index.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{dilated, index_of};
+/// let idx = dilated::sub::<_, 2>(index_of([5u8, 9u8]), 0, 3);
+/// assert_eq!(idx, index_of([2u8, 9u8]));
+/// ```
+#[inline]
+pub fn sub<I, const N: usize>(index: I, axis: usize, value: <I as Deinterleave<N>>::Output) -> I
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingSub,
+    <I as Deinterleave<N>>::Output: Interleave<N, Output = I>,
+{
+    let mask = mask::<I, N>(axis);
+    dilated_sub(index, value.interleave() << axis, mask) | (index & !mask)
+}
+
+/// Compares the coordinates of `axis` of two indexes.
+///
+/// # Examples
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use zorder::{dilated, index_of};
+/// let a = index_of([1u16, 8u16]);
+/// let b = index_of([2u16, 3u16]);
+///
+/// assert_eq!(dilated::cmp::<_, 2>(a, b, 0), Ordering::Less);
+/// assert_eq!(dilated::cmp::<_, 2>(a, b, 1), Ordering::Greater);
+/// ```
+#[inline]
+pub fn cmp<I, const N: usize>(a: I, b: I, axis: usize) -> Ordering
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned,
+{
+    let mask = mask::<I, N>(axis);
+    (a & mask).cmp(&(b & mask))
+}
+
+/// Returns the index following `index` on the Z-order curve together with
+/// its coordinates, updating the given coordinates of `index` incrementally
+/// instead of decoding the next index.
+///
+/// `coord` must be the coordinates of `index`. Returns `None` if `index` is
+/// the last index on the curve.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{dilated, index_of};
+/// let coord = [1u8, 1u8];
+/// let idx = index_of(coord);
+///
+/// let (idx, coord) = dilated::successor(idx, coord).unwrap();
+/// assert_eq!(coord, [2u8, 0u8]);
+/// assert_eq!(idx, index_of(coord));
+/// ```
+///
+/// ```compile_fail
+/// # use zorder::{dilated, index_of};
+/// let coord = [127i8, -1i8];
+/// let next = dilated::successor(index_of(coord), coord);
+/// ```
+#[inline]
+pub fn successor<I, const N: usize>(
+    index: I,
+    mut coord: [<I as Deinterleave<N>>::Output; N],
+) -> Option<(I, [<I as Deinterleave<N>>::Output; N])>
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned,
+{
+    // Incrementing the index clears the trailing ones and sets the next bit.
+    // The cleared bits are the lowest bits of each coordinate, and the set
+    // bit is the next bit of one coordinate.
+    let trailing = index.trailing_ones() as usize;
+    let bits = <<I as Deinterleave<N>>::Output as BitCount>::BITS as usize;
+    if trailing >= N * bits {
+        return None;
+    }
+
+    let carry_axis = trailing % N;
+    for (axis, value) in coord.iter_mut().enumerate() {
+        if axis == carry_axis {
+            *value = *value + num_traits::One::one();
+        } else {
+            let cleared = (trailing + N - 1 - axis) / N;
+            if cleared >= bits {
+                *value = num_traits::Zero::zero();
+            } else {
+                *value = *value >> cleared << cleared;
+            }
+        }
+    }
+
+    Some((index + I::one(), coord))
+}
+
+/// Single coordinate stored in the interleaved form of the first axis of an
+/// index with `N` axes, also known as a dilated integer.
+///
+/// Arithmetic and comparisons work without converting the coordinate back,
+/// and the value can be combined into an index with [`to_index`](Self::to_index).
+/// Arithmetic wraps around at the bounds of the coordinate type, and adding
+/// or subtracting two values requires an unsigned coordinate type.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{dilated::Dilated, index_of};
+/// let x = Dilated::<u8, 2>::new(5);
+/// let y = Dilated::<u8, 2>::new(9);
+///
+/// let idx = (x + Dilated::new(1)).to_index(0) | y.to_index(1);
+/// assert_eq!(idx, index_of([6u8, 9u8]));
+/// ```
+pub struct Dilated<I, const N: usize>
+where
+    I: Interleave<N>,
+{
+    value: <I as Interleave<N>>::Output,
+}
+
+impl<I, const N: usize> Dilated<I, N>
+where
+    I: Interleave<N>,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I>,
+{
+    /// Interleaves the given coordinate.
+    #[inline]
+    pub fn new(value: I) -> Self {
+        Self {
+            value: value.interleave(),
+        }
+    }
+
+    /// Extracts the coordinate of `axis` from the given index.
+    #[inline]
+    pub fn from_index(index: <I as Interleave<N>>::Output, axis: usize) -> Self {
+        Self {
+            value: (index & mask::<_, N>(axis)) >> axis,
+        }
+    }
+
+    /// Returns the coordinate.
+    #[inline]
+    pub fn get(self) -> I {
+        self.value.deinterleave(0)
+    }
+
+    /// Returns the interleaved bits of the coordinate.
+    #[inline]
+    pub fn into_inner(self) -> <I as Interleave<N>>::Output {
+        self.value
+    }
+
+    /// Returns the coordinate as the given `axis` of an index, with all other
+    /// axes set to zero.
+    #[inline]
+    pub fn to_index(self, axis: usize) -> <I as Interleave<N>>::Output {
+        self.value << axis
+    }
+}
+
+impl<I, const N: usize> Dilated<I, N>
+where
+    I: Interleave<N>,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I> + WrappingAdd + WrappingSub,
+{
+    /// Adds one to the coordinate.
+    #[inline]
+    pub fn increment(self) -> Self {
+        Self {
+            value: increment::<_, N>(self.value, 0),
+        }
+    }
+
+    /// Subtracts one from the coordinate.
+    #[inline]
+    pub fn decrement(self) -> Self {
+        Self {
+            value: decrement::<_, N>(self.value, 0),
+        }
+    }
+}
+
+impl<I, const N: usize> Add for Dilated<I, N>
+where
+    I: Interleave<N> + Unsigned,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I> + WrappingAdd,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        let mask = mask::<<I as Interleave<N>>::Output, N>(0);
+        Self {
+            value: dilated_add(self.value, rhs.value, mask),
+        }
+    }
+}
+
+impl<I, const N: usize> Sub for Dilated<I, N>
+where
+    I: Interleave<N> + Unsigned,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I> + WrappingSub,
+{
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mask = mask::<<I as Interleave<N>>::Output, N>(0);
+        Self {
+            value: dilated_sub(self.value, rhs.value, mask),
+        }
+    }
+}
+
+impl<I, const N: usize> Clone for Dilated<I, N>
+where
+    I: Interleave<N>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<I, const N: usize> Copy for Dilated<I, N> where I: Interleave<N> {}
+
+impl<I, const N: usize> PartialEq for Dilated<I, N>
+where
+    I: Interleave<N>,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<I, const N: usize> Eq for Dilated<I, N> where I: Interleave<N> {}
+
+impl<I, const N: usize> PartialOrd for Dilated<I, N>
+where
+    I: Interleave<N>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<I, const N: usize> Ord for Dilated<I, N>
+where
+    I: Interleave<N>,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<I, const N: usize> core::fmt::Debug for Dilated<I, N>
+where
+    I: Interleave<N> + core::fmt::Debug,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Dilated").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coord_of, index_of};
+
+    #[test]
+    fn increment_and_decrement_all_axes() {
+        for idx in (0..u32::MAX).step_by(104_729) {
+            let coord: [u8; 4] = coord_of(idx);
+
+            for axis in 0..4 {
+                let mut next = coord;
+                next[axis] = next[axis].wrapping_add(1);
+                assert_eq!(increment::<_, 4>(idx, axis), index_of(next));
+
+                let mut prev = coord;
+                prev[axis] = prev[axis].wrapping_sub(1);
+                assert_eq!(decrement::<_, 4>(idx, axis), index_of(prev));
+            }
+        }
+    }
+
+    #[test]
+    fn increment_and_decrement_signed() {
+        for x in i8::MIN..=i8::MAX {
+            for y in [i8::MIN, -1, 0, i8::MAX] {
+                let idx = index_of([x, y]);
+
+                assert_eq!(increment::<_, 2>(idx, 0), index_of([x.wrapping_add(1), y]));
+                assert_eq!(increment::<_, 2>(idx, 1), index_of([x, y.wrapping_add(1)]));
+                assert_eq!(decrement::<_, 2>(idx, 0), index_of([x.wrapping_sub(1), y]));
+                assert_eq!(decrement::<_, 2>(idx, 1), index_of([x, y.wrapping_sub(1)]));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "axis 3 out of range for 3 dimensions")]
+    fn axis_out_of_range() {
+        increment::<_, 3>(0u32, 3);
+    }
+
+    #[test]
+    fn add_and_sub_unused_bits() {
+        // `u32` holds only 24 bits of three `u8` coordinates.
+        let idx = index_of([250u8, 3u8, 128u8]);
+
+        assert_eq!(add::<_, 3>(idx, 0, 10), index_of([4u8, 3u8, 128u8]));
+        assert_eq!(add::<_, 3>(idx, 2, 128), index_of([250u8, 3u8, 0u8]));
+        assert_eq!(sub::<_, 3>(idx, 1, 4), index_of([250u8, 255u8, 128u8]));
+    }
+
+    #[test]
+    fn compare_axes() {
+        let a = index_of([100u16, 7u16, 0u16]);
+        let b = index_of([99u16, 7u16, 1u16]);
+
+        assert_eq!(cmp::<_, 3>(a, b, 0), Ordering::Greater);
+        assert_eq!(cmp::<_, 3>(a, b, 1), Ordering::Equal);
+        assert_eq!(cmp::<_, 3>(a, b, 2), Ordering::Less);
+    }
+
+    #[test]
+    fn successor_matches_coord_of() {
+        let mut idx = 0u16;
+        let mut coord: [u8; 2] = coord_of(idx);
+
+        while let Some((next, next_coord)) = successor(idx, coord) {
+            assert_eq!(next, idx + 1);
+            assert_eq!(next_coord, coord_of::<_, 2>(next));
+            (idx, coord) = (next, next_coord);
+        }
+        assert_eq!(idx, u16::MAX);
+
+        // `u32` holds only 24 bits of three `u8` coordinates.
+        let mut idx = 0x00FF_0000u32;
+        let mut coord: [u8; 3] = coord_of(idx);
+
+        while let Some((next, next_coord)) = successor(idx, coord) {
+            assert_eq!(next, idx + 1);
+            assert_eq!(next_coord, coord_of::<_, 3>(next));
+            (idx, coord) = (next, next_coord);
+        }
+        assert_eq!(idx, 0x00FF_FFFF);
+    }
+
+    #[test]
+    fn dilated_arithmetic() {
+        let a = Dilated::<u16, 3>::new(40_000);
+        let b = Dilated::<u16, 3>::new(30_000);
+
+        assert_eq!((a + b).get(), 40_000u16.wrapping_add(30_000));
+        assert_eq!((b - a).get(), 30_000u16.wrapping_sub(40_000));
+        assert_eq!(a.increment().get(), 40_001);
+        assert_eq!(Dilated::<u16, 3>::new(0).decrement().get(), u16::MAX);
+        assert!(b < a);
+
+        let idx = index_of([1u16, 2u16, 3u16]);
+        assert_eq!(Dilated::<u16, 3>::from_index(idx, 1).get(), 2);
+    }
+}
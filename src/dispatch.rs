@@ -0,0 +1,542 @@
+//! This module provides Z-order curve conversions which pick the fastest
+//! available implementation at runtime.
+//!
+//! CPU features are detected once on the first conversion and the result is
+//! cached. If the `bmi2` target feature is enabled at compile time, for
+//! example with `-C target-cpu=native`, the conversions compile straight to
+//! the [`bmi2`](crate::bmi2) implementation without any checks.
+//!
+//! Detection uses the standard library. In `no_std` builds, the software
+//! implementation is used unless a detection hook is installed with
+//! [`set_detection_hook`].
+//!
+//! Supporting `bmi2` does not mean that it is fast. AMD CPUs before Zen 3
+//! execute `pdep` and `pext` in microcode, which is much slower than the
+//! software implementation, so the software implementation is selected on
+//! them. For finer control, [`calibrate`] measures both implementations for
+//! each coordinate type and dimension and selects the faster one.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::dispatch::{self, coord_of, index_of};
+//!
+//! let idx = index_of([3u32, 7u32]);
+//! assert_eq!(idx, 0b101_111u64);
+//!
+//! let coord = coord_of(idx);
+//! assert_eq!(coord, [3u32, 7u32]);
+//!
+//! // Selected implementation can be logged.
+//! println!("zorder backend: {}", dispatch::backend());
+//! ```
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicPtr, AtomicU8, Ordering},
+};
+
+use crate::{
+    bmi2::{DeinterleaveBMI2, InterleaveBMI2},
+    mask::BitCount,
+    Deinterleave, Interleave,
+};
+
+/// Implementation used by the conversions of this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// Software implementation supported by all platforms.
+    Software,
+    /// Implementation using the `bmi2` instruction set.
+    Bmi2,
+}
+
+impl Backend {
+    /// Returns the name of the backend.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Software => "software",
+            Self::Bmi2 => "bmi2",
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Cached backend; one of the `STATE_*` constants.
+static STATE: AtomicU8 = AtomicU8::new(STATE_UNKNOWN);
+/// Calibrated backends of [`index_of`] for each coordinate size and
+/// dimension; one of the `STATE_*` constants. See [`calibration_slot`].
+static INDEX_OF_STATES: [AtomicU8; CALIBRATION_SLOTS] =
+    [const { AtomicU8::new(STATE_UNKNOWN) }; CALIBRATION_SLOTS];
+/// Calibrated backends of [`coord_of`] for each coordinate size and
+/// dimension; one of the `STATE_*` constants. See [`calibration_slot`].
+static COORD_OF_STATES: [AtomicU8; CALIBRATION_SLOTS] =
+    [const { AtomicU8::new(STATE_UNKNOWN) }; CALIBRATION_SLOTS];
+/// Detection hook installed with [`set_detection_hook`], or null.
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+const STATE_UNKNOWN: u8 = 0;
+const STATE_SOFTWARE: u8 = 1;
+const STATE_BMI2: u8 = 2;
+
+/// Coordinates are 8, 16, 32 or 64 bits wide and there are at most 16 of
+/// them.
+const CALIBRATION_SLOTS: usize = 4 * 17;
+
+/// Returns the calibration slot of `dim` coordinates of `coord_bits` bits.
+#[inline]
+fn calibration_slot(coord_bits: u32, dim: usize) -> usize {
+    (coord_bits.trailing_zeros() as usize - 3) * 17 + dim
+}
+
+/// Returns the number of bits of each coordinate interleaved into an index
+/// of type `I` in dimension `N`.
+///
+/// The slot depends only on the index type, so that coordinate types which
+/// are interleaved in place of an integer, such as
+/// [`Lane`](crate::lane::Lane) types, share the slot of the integer. Index
+/// types are the smallest ones which fit the coordinates, so the coordinate
+/// width is the largest power of two which fits `N` times.
+#[inline]
+fn coord_bits<I: BitCount, const N: usize>() -> u32 {
+    1 << (<I as BitCount>::BITS / N as u32).ilog2()
+}
+
+/// Returns the calibrated backend of the slot, or [`backend`] if the slot is
+/// not calibrated.
+#[inline]
+fn calibrated_backend(states: &[AtomicU8; CALIBRATION_SLOTS], slot: usize) -> Backend {
+    match states[slot].load(Ordering::Relaxed) {
+        STATE_SOFTWARE => Backend::Software,
+        STATE_BMI2 => Backend::Bmi2,
+        _ => backend(),
+    }
+}
+
+/// Returns the backend used by the conversions of this module, unless
+/// [`calibrate`] selected another one for the coordinate type and dimension.
+///
+/// Detection is done on the first call and cached.
+#[inline]
+pub fn backend() -> Backend {
+    if cfg!(all(target_arch = "x86_64", target_feature = "bmi2")) {
+        return Backend::Bmi2;
+    }
+
+    let state = match STATE.load(Ordering::Relaxed) {
+        STATE_UNKNOWN => {
+            let state = if detect_bmi2() {
+                STATE_BMI2
+            } else {
+                STATE_SOFTWARE
+            };
+            STATE.store(state, Ordering::Relaxed);
+            state
+        }
+        state => state,
+    };
+
+    if state == STATE_BMI2 {
+        Backend::Bmi2
+    } else {
+        Backend::Software
+    }
+}
+
+/// Returns the backend used by [`index_of`] for coordinates of type `I` in
+/// dimension `N`.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::dispatch;
+/// println!("index_of([u16; 3]) uses {}", dispatch::index_of_backend::<u16, 3>());
+/// ```
+#[inline]
+pub fn index_of_backend<I, const N: usize>() -> Backend
+where
+    I: InterleaveBMI2<N>,
+{
+    if cfg!(all(target_arch = "x86_64", target_feature = "bmi2")) {
+        return Backend::Bmi2;
+    }
+    let coord_bits = coord_bits::<<I as Interleave<N>>::Output, N>();
+    calibrated_backend(&INDEX_OF_STATES, calibration_slot(coord_bits, N))
+}
+
+/// Returns the backend used by [`coord_of`] for indexes of type `I` in
+/// dimension `N`.
+#[inline]
+pub fn coord_of_backend<I, const N: usize>() -> Backend
+where
+    I: DeinterleaveBMI2<N>,
+{
+    if cfg!(all(target_arch = "x86_64", target_feature = "bmi2")) {
+        return Backend::Bmi2;
+    }
+    let coord_bits = <<I as Deinterleave<N>>::Output as BitCount>::BITS;
+    calibrated_backend(&COORD_OF_STATES, calibration_slot(coord_bits, N))
+}
+
+/// Installs a hook which tells whether the CPU supports the `bmi2`
+/// instruction set. The hook replaces the standard library based detection,
+/// and it is called once on the next conversion. CPUs with slow `bmi2`
+/// instructions are still detected and use the software implementation.
+///
+/// This is mainly useful in `no_std` builds, where the CPU features cannot
+/// be detected otherwise. Backends selected by [`calibrate`] are discarded.
+///
+/// # Safety
+///
+/// The hook must return true only if the `bmi2` x86_64 feature is supported
+/// by the CPU.
+pub unsafe fn set_detection_hook(hook: fn() -> bool) {
+    HOOK.store(hook as *mut (), Ordering::Relaxed);
+    STATE.store(STATE_UNKNOWN, Ordering::Relaxed);
+    for state in INDEX_OF_STATES.iter().chain(&COORD_OF_STATES) {
+        state.store(STATE_UNKNOWN, Ordering::Relaxed);
+    }
+}
+
+/// Detects fast `bmi2` support.
+#[cold]
+fn detect_bmi2() -> bool {
+    has_bmi2() && !has_microcoded_bmi2()
+}
+
+/// Detects `bmi2` support with the installed hook or the standard library.
+fn has_bmi2() -> bool {
+    if !cfg!(target_arch = "x86_64") {
+        return false;
+    }
+
+    let hook = HOOK.load(Ordering::Relaxed);
+    if hook.is_null() {
+        crate::bmi2::has_hardware_support()
+    } else {
+        // SAFETY: Only `fn() -> bool` pointers are stored in `HOOK`.
+        let hook = unsafe { core::mem::transmute::<*mut (), fn() -> bool>(hook) };
+        hook()
+    }
+}
+
+/// Returns true if the CPU executes `pdep` and `pext` in microcode.
+fn has_microcoded_bmi2() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use core::arch::x86_64::__cpuid;
+
+        // Leaves 0 and 1 are supported by all x86_64 CPUs.
+        let leaf0 = __cpuid(0);
+        let leaf1 = __cpuid(1);
+        let mut vendor = [0u8; 12];
+        vendor[..4].copy_from_slice(&leaf0.ebx.to_le_bytes());
+        vendor[4..8].copy_from_slice(&leaf0.edx.to_le_bytes());
+        vendor[8..].copy_from_slice(&leaf0.ecx.to_le_bytes());
+
+        is_microcoded_bmi2(&vendor, cpu_family(leaf1.eax))
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// Returns the CPU family from the signature in `eax` of `cpuid` leaf 1.
+fn cpu_family(signature: u32) -> u32 {
+    let family = (signature >> 8) & 0xF;
+    if family == 0xF {
+        family + ((signature >> 20) & 0xFF)
+    } else {
+        family
+    }
+}
+
+/// Returns true if CPUs of the vendor and family execute `pdep` and `pext` in
+/// microcode. These are AMD CPUs before Zen 3 (family `0x19`) and Hygon CPUs,
+/// which are based on Zen 1.
+fn is_microcoded_bmi2(vendor: &[u8; 12], family: u32) -> bool {
+    match vendor {
+        b"AuthenticAMD" => family < 0x19,
+        b"HygonGenuine" => true,
+        _ => false,
+    }
+}
+
+/// Measures the software and `bmi2` implementations of each coordinate type
+/// and dimension combination, and selects the faster one for the
+/// conversions of this module.
+///
+/// This takes a few milliseconds, so it should be called once at startup.
+/// It has no effect if the CPU does not support `bmi2` or if the `bmi2`
+/// target feature is enabled at compile time.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::dispatch;
+/// dispatch::calibrate();
+/// println!("index_of([u32; 2]) uses {}", dispatch::index_of_backend::<u32, 2>());
+/// ```
+#[cfg(feature = "std")]
+pub fn calibrate() {
+    #[cfg(target_arch = "x86_64")]
+    if has_bmi2() {
+        calibration::calibrate_all();
+    }
+}
+
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+mod calibration {
+    use core::hint::black_box;
+    use std::{
+        time::{Duration, Instant},
+        vec::Vec,
+    };
+
+    use num_traits::{cast::AsPrimitive, PrimInt};
+
+    use super::*;
+
+    /// Number of conversions in one measurement.
+    const SAMPLES: u64 = 1024;
+    /// Number of measurements of which the fastest is used.
+    const ROUNDS: usize = 8;
+
+    pub(super) fn calibrate_all() {
+        macro_rules! calibrate {
+            ($($dim:expr, $coord_type:ty => $index_type:ty);*) => {
+                $(
+                    calibrate_one::<$coord_type, $index_type, $dim>();
+                )*
+            };
+        }
+
+        calibrate! {
+            2, u8 => u16;
+            3, u8 => u32;
+            4, u8 => u32;
+            5, u8 => u64;
+            6, u8 => u64;
+            7, u8 => u64;
+            8, u8 => u64;
+            9, u8 => u128;
+            10, u8 => u128;
+            11, u8 => u128;
+            12, u8 => u128;
+            13, u8 => u128;
+            14, u8 => u128;
+            15, u8 => u128;
+            16, u8 => u128;
+            2, u16 => u32;
+            3, u16 => u64;
+            4, u16 => u64;
+            5, u16 => u128;
+            6, u16 => u128;
+            7, u16 => u128;
+            8, u16 => u128;
+            2, u32 => u64;
+            3, u32 => u128;
+            4, u32 => u128;
+            2, u64 => u128
+        }
+    }
+
+    fn calibrate_one<C, I, const N: usize>()
+    where
+        C: InterleaveBMI2<N, Output = I> + BitCount + Copy,
+        I: DeinterleaveBMI2<N> + Deinterleave<N, Output = C> + PrimInt + 'static,
+        u64: AsPrimitive<I>,
+    {
+        let indexes = || (0..SAMPLES).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15).as_());
+        let coords: Vec<[C; N]> = indexes().map(crate::coord_of).collect();
+
+        let software = fastest(|| {
+            for &coord in &coords {
+                black_box(crate::index_of(black_box(coord)));
+            }
+        });
+        // SAFETY: Calibration is done only if the CPU supports `bmi2`.
+        let bmi2 = fastest(|| unsafe {
+            for &coord in &coords {
+                black_box(crate::bmi2::index_of_unchecked(black_box(coord)));
+            }
+        });
+        let index_of = if bmi2 < software {
+            Backend::Bmi2
+        } else {
+            Backend::Software
+        };
+
+        let software = fastest(|| {
+            for index in indexes() {
+                black_box(crate::coord_of::<I, N>(black_box(index)));
+            }
+        });
+        // SAFETY: Calibration is done only if the CPU supports `bmi2`.
+        let bmi2 = fastest(|| unsafe {
+            for index in indexes() {
+                black_box(crate::bmi2::coord_of_unchecked::<I, N>(black_box(index)));
+            }
+        });
+        let coord_of = if bmi2 < software {
+            Backend::Bmi2
+        } else {
+            Backend::Software
+        };
+
+        let slot = calibration_slot(<C as BitCount>::BITS, N);
+        INDEX_OF_STATES[slot].store(backend_state(index_of), Ordering::Relaxed);
+        COORD_OF_STATES[slot].store(backend_state(coord_of), Ordering::Relaxed);
+    }
+
+    /// Converts a backend to one of the `STATE_*` constants.
+    fn backend_state(backend: Backend) -> u8 {
+        match backend {
+            Backend::Software => STATE_SOFTWARE,
+            Backend::Bmi2 => STATE_BMI2,
+        }
+    }
+
+    /// Returns the fastest duration of running `f` [`ROUNDS`] times.
+    fn fastest(mut f: impl FnMut()) -> Duration {
+        (0..ROUNDS)
+            .map(|_| {
+                let start = Instant::now();
+                f();
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// Calculates Z-order curve index for given sequence of coordinates using
+/// the fastest available implementation.
+///
+/// Output type will be the smallest integer type that can hold all of the
+/// given coordinates, and it is signed for signed coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::dispatch::index_of;
+/// let idx = index_of([3u32, 7u32]);
+/// assert_eq!(idx, 0b101_111u64);
+/// ```
+#[inline]
+pub fn index_of<I, const N: usize>(array: [I; N]) -> <I as Interleave<N>>::Output
+where
+    I: InterleaveBMI2<N>,
+{
+    #[cfg(target_arch = "x86_64")]
+    if index_of_backend::<I, N>() == Backend::Bmi2 {
+        // SAFETY: `Bmi2` backend is selected only if the `bmi2` instruction
+        // set is supported by the CPU.
+        return unsafe { crate::bmi2::index_of_unchecked(array) };
+    }
+
+    crate::index_of(array)
+}
+
+/// Returns the N-dimensional coordinates of the given Z-order curve index
+/// using the fastest available implementation.
+///
+/// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
+/// you may need to specify the number of dimensions `N` to disambiguate.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::dispatch::coord_of;
+/// let coord = coord_of(0b101_111u64);
+/// assert_eq!(coord, [3u32, 7u32]);
+/// ```
+#[inline]
+pub fn coord_of<I, const N: usize>(index: I) -> [<I as Deinterleave<N>>::Output; N]
+where
+    I: DeinterleaveBMI2<N> + Copy,
+{
+    #[cfg(target_arch = "x86_64")]
+    if coord_of_backend::<I, N>() == Backend::Bmi2 {
+        // SAFETY: `Bmi2` backend is selected only if the `bmi2` instruction
+        // set is supported by the CPU.
+        return unsafe { crate::bmi2::coord_of_unchecked(index) };
+    }
+
+    crate::coord_of(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_matches_detection() {
+        let expected = if crate::bmi2::has_hardware_support() && !has_microcoded_bmi2() {
+            Backend::Bmi2
+        } else {
+            Backend::Software
+        };
+        assert_eq!(backend(), expected);
+        assert_eq!(backend().name(), expected.name());
+    }
+
+    #[test]
+    fn calibration_slots() {
+        assert_eq!(coord_bits::<u32, 3>(), 8);
+        assert_eq!(coord_bits::<u64, 5>(), 8);
+        assert_eq!(coord_bits::<u128, 16>(), 8);
+        assert_eq!(coord_bits::<u128, 5>(), 16);
+        assert_eq!(coord_bits::<u128, 3>(), 32);
+        assert_eq!(coord_bits::<u128, 2>(), 64);
+
+        assert_eq!(calibration_slot(8, 2), 2);
+        assert_eq!(calibration_slot(64, 16), CALIBRATION_SLOTS - 1);
+    }
+
+    #[test]
+    fn microcoded_cpus() {
+        // Zen 2, Zen 3 and an Intel CPU.
+        assert_eq!(cpu_family(0x0087_0F10), 0x17);
+        assert_eq!(cpu_family(0x00A2_0F10), 0x19);
+        assert_eq!(cpu_family(0x0009_06EA), 0x6);
+
+        assert!(is_microcoded_bmi2(b"AuthenticAMD", 0x15));
+        assert!(is_microcoded_bmi2(b"AuthenticAMD", 0x17));
+        assert!(!is_microcoded_bmi2(b"AuthenticAMD", 0x19));
+        assert!(!is_microcoded_bmi2(b"AuthenticAMD", 0x1A));
+        assert!(is_microcoded_bmi2(b"HygonGenuine", 0x18));
+        assert!(!is_microcoded_bmi2(b"GenuineIntel", 0x6));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn calibrated_conversions() {
+        calibrate();
+
+        for i in 0..10_000u64 {
+            let coord: [u8; 5] = crate::coord_of(i);
+            assert_eq!(index_of(coord), crate::index_of(coord));
+            assert_eq!(coord_of::<_, 5>(i), coord);
+        }
+    }
+
+    #[test]
+    fn matches_software() {
+        for i in 0..10_000u64 {
+            let coord: [u16; 3] = crate::coord_of(i);
+            assert_eq!(index_of(coord), crate::index_of(coord));
+            assert_eq!(coord_of::<_, 3>(i), coord);
+
+            let coord: [i32; 2] = crate::coord_of(i as i64);
+            assert_eq!(index_of(coord), crate::index_of(coord));
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use core::fmt;
+
+/// Error returned by the fallible conversions of this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// Coordinate of the given axis is outside of the range supported by the
+    /// conversion.
+    CoordinateOutOfRange {
+        /// Axis of the first coordinate which is out of range.
+        axis: usize,
+    },
+    /// Index is outside of the range supported by the conversion.
+    IndexOutOfRange,
+    /// Number of bits per coordinate is larger than the coordinate type.
+    BitsOutOfRange {
+        /// Requested number of bits per coordinate.
+        bits: u32,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CoordinateOutOfRange { axis } => {
+                write!(f, "coordinate of axis {axis} is out of range")
+            }
+            Self::IndexOutOfRange => f.write_str("index is out of range"),
+            Self::BitsOutOfRange { bits } => {
+                write!(f, "{bits} bits per coordinate is out of range")
+            }
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,224 @@
+//! This module provides support for floating-point coordinates.
+//!
+//! `f32` and `f64` coordinates can be passed directly to
+//! [`index_of`](crate::index_of) and [`bmi2::index_of`](crate::bmi2::index_of),
+//! and they are interleaved like `u32` and `u64` coordinates. Since the
+//! output index types cannot tell apart floating-point and integer
+//! coordinates, decoding is done with [`coord_of`] of this module.
+//!
+//! Before interleaving, the bits of each coordinate are mapped so that
+//! unsigned integer order matches [`f32::total_cmp`] and [`f64::total_cmp`]:
+//! the sign bit of positive numbers is flipped and all bits of negative
+//! numbers are flipped. Sorted indexes are therefore a Z-order curve over
+//! the IEEE 754 total order, where
+//!
+//! - negative numbers come before positive numbers and `-0.0` comes right
+//!   before `+0.0`,
+//! - positive NaNs come after `+inf` and negative NaNs come before `-inf`.
+//!
+//! Mapping is a bijection, so decoding returns the exact bits of the
+//! encoded coordinates, including the sign of zero and NaN payloads.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::{float::coord_of, index_of};
+//!
+//! let idx = index_of([-1.5f32, 2.0f32]);
+//! assert!(index_of([-2.0f32, -2.0f32]) < idx);
+//!
+//! let coord: [f32; 2] = coord_of(idx);
+//! assert_eq!(coord, [-1.5f32, 2.0f32]);
+//! ```
+
+use num_traits::PrimInt;
+
+use crate::{mask::BitCount, Deinterleave};
+
+/// Maps floating-point numbers to unsigned integers of the same width, so
+/// that the integer order matches the IEEE 754 total order.
+pub trait TotalOrder: private::Sealed + Copy {
+    /// Unsigned integer type with the same width as the float.
+    type Bits: BitCount + PrimInt;
+
+    /// Maps the float to an unsigned integer in total order.
+    fn to_ordered_bits(self) -> Self::Bits;
+
+    /// Inverse of [`TotalOrder::to_ordered_bits`].
+    fn from_ordered_bits(bits: Self::Bits) -> Self;
+}
+
+macro_rules! impl_total_order {
+    ($($impl_type:ty => $bits:ty);*) => {
+        $(
+            impl TotalOrder for $impl_type {
+                type Bits = $bits;
+
+                #[inline]
+                fn to_ordered_bits(self) -> Self::Bits {
+                    let bits = self.to_bits();
+                    if bits >> (<$bits>::BITS - 1) == 0 {
+                        bits ^ (1 << (<$bits>::BITS - 1))
+                    } else {
+                        !bits
+                    }
+                }
+
+                #[inline]
+                fn from_ordered_bits(bits: Self::Bits) -> Self {
+                    let bits = if bits >> (<$bits>::BITS - 1) == 0 {
+                        !bits
+                    } else {
+                        bits ^ (1 << (<$bits>::BITS - 1))
+                    };
+                    <$impl_type>::from_bits(bits)
+                }
+            }
+        )*
+    };
+}
+
+impl_total_order! {
+    f32 => u32;
+    f64 => u64
+}
+
+/// Returns the N-dimensional floating-point coordinates of the given Z-order
+/// curve index.
+///
+/// The coordinate type `F` usually needs to be specified, since the same
+/// index types are used for integer coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{float::coord_of, index_of};
+/// let coord: [f32; 3] = coord_of(index_of([0.5f32, -0.0f32, f32::INFINITY]));
+/// assert_eq!(coord, [0.5f32, -0.0f32, f32::INFINITY]);
+/// assert!(coord[1].is_sign_negative());
+/// ```
+#[inline]
+pub fn coord_of<F, I, const N: usize>(index: I) -> [F; N]
+where
+    F: TotalOrder,
+    I: Deinterleave<N, Output = <F as TotalOrder>::Bits> + Copy,
+{
+    crate::coord_of(index).map(F::from_ordered_bits)
+}
+
+/// `bmi2` module provides floating-point coordinate calculations using the
+/// bmi2 instruction set.
+///
+/// The functions mirror [`zorder::bmi2`](crate::bmi2) and have the same
+/// safety requirements.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{bmi2::HardwareSupportToken, float};
+/// if let Some(support_token) = HardwareSupportToken::new() {
+///     let idx = zorder::bmi2::index_of([1.0f32, -1.0f32], support_token);
+///     let coord: [f32; 2] = float::bmi2::coord_of(idx, support_token);
+///     assert_eq!(coord, [1.0f32, -1.0f32]);
+/// }
+/// ```
+pub mod bmi2 {
+    use super::TotalOrder;
+    use crate::bmi2::{DeinterleaveBMI2, HardwareSupportToken};
+    use crate::Deinterleave;
+
+    /// Safe wrapper around [`coord_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn coord_of<F, I, const N: usize>(index: I, support_token: HardwareSupportToken) -> [F; N]
+    where
+        F: TotalOrder,
+        I: DeinterleaveBMI2<N> + Deinterleave<N, Output = <F as TotalOrder>::Bits> + Copy,
+    {
+        crate::bmi2::coord_of(index, support_token).map(F::from_ordered_bits)
+    }
+
+    /// Returns the N-dimensional floating-point coordinates of the given
+    /// Z-order curve index.
+    ///
+    /// This function requires the `bmi2` instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU.
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn coord_of_unchecked<F, I, const N: usize>(index: I) -> [F; N]
+    where
+        F: TotalOrder,
+        I: DeinterleaveBMI2<N> + Deinterleave<N, Output = <F as TotalOrder>::Bits> + Copy,
+    {
+        crate::bmi2::coord_of_unchecked(index).map(F::from_ordered_bits)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index_of;
+
+    const VALUES: [f32; 10] = [
+        f32::NEG_INFINITY,
+        -1.5,
+        -f32::MIN_POSITIVE,
+        -0.0,
+        0.0,
+        f32::MIN_POSITIVE,
+        1.0,
+        f32::MAX,
+        f32::INFINITY,
+        f32::NAN,
+    ];
+
+    #[test]
+    fn ordered_bits_follow_total_order() {
+        for a in VALUES.into_iter().chain([-f32::NAN]) {
+            for b in VALUES.into_iter().chain([-f32::NAN]) {
+                assert_eq!(
+                    a.to_ordered_bits().cmp(&b.to_ordered_bits()),
+                    a.total_cmp(&b)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn index_and_back_bit_exact() {
+        let payload_nan = f64::from_bits(0xFFF0_0000_0000_BEEF);
+        for x in VALUES.into_iter().chain([-f32::NAN]) {
+            for y in VALUES {
+                let coord: [f32; 2] = coord_of(index_of([x, y]));
+                assert_eq!(coord.map(f32::to_bits), [x.to_bits(), y.to_bits()]);
+
+                let coord: [f64; 2] = coord_of(index_of([x as f64, payload_nan]));
+                assert_eq!(
+                    coord.map(f64::to_bits),
+                    [(x as f64).to_bits(), payload_nan.to_bits()]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn negative_coordinates_come_first() {
+        assert!(index_of([-1.0f32, -1.0f32]) < index_of([-0.0f32, -0.0f32]));
+        assert!(index_of([-0.0f32, -0.0f32]) < index_of([0.0f32, 0.0f32]));
+        assert!(index_of([0.0f64, 0.0f64]) < index_of([1.0f64, 1.0f64]));
+    }
+}
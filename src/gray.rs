@@ -0,0 +1,312 @@
+//! This module provides functions to convert N-dimensional coordinates to
+//! Gray-coded Z-order curve indexes and back.
+//!
+//! The Gray-coded curve, also known as the Faloutsos curve, reads the
+//! interleaved bits of the coordinates as a
+//! [Gray code](https://en.wikipedia.org/wiki/Gray_code), and the index is
+//! the position of that code in the Gray code sequence. Consecutive indexes
+//! differ in exactly one bit of one coordinate, which often improves
+//! clustering compared to the Z-order curve.
+//!
+//! The conversions build on the Z-order curve conversions, so the output
+//! types are the same as with [`index_of`](crate::index_of), and the
+//! interleaving can be accelerated with the `bmi2` instruction set using the
+//! [`bmi2`](crate::gray::bmi2) submodule.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::gray::{coord_of, index_of};
+//!
+//! let idx = index_of([1u16, 1u16]);
+//! assert_eq!(idx, 2u32);
+//!
+//! let coord = coord_of(idx);
+//! assert_eq!(coord, [1u16, 1u16]);
+//! ```
+
+use num_traits::PrimInt;
+
+use crate::{Deinterleave, Interleave};
+
+/// Calculates Gray-coded curve index for given sequence of coordinates.
+///
+/// Output type will be the smallest integer type that can hold all of the
+/// given coordinates, and it is signed for signed coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::gray::index_of;
+/// let idx = index_of([1u8, 0u8, 0u8]);
+/// assert_eq!(idx, 1u32);
+///
+/// let idx = index_of([0u8, 1u8, 0u8]);
+/// assert_eq!(idx, 3u32);
+/// ```
+#[inline]
+pub fn index_of<I, const N: usize>(array: [I; N]) -> <I as Interleave<N>>::Output
+where
+    I: Interleave<N>,
+{
+    gray_decode(crate::index_of(array))
+}
+
+/// Returns the N-dimensional coordinates of the given Gray-coded curve index.
+///
+/// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
+/// you may need to specify the number of dimensions `N` to disambiguate.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::gray::coord_of;
+/// let coord = coord_of(3u32);
+/// assert_eq!(coord, [0u8, 1u8, 0u8]);
+/// ```
+#[inline]
+pub fn coord_of<I, const N: usize>(index: I) -> [<I as Deinterleave<N>>::Output; N]
+where
+    I: Deinterleave<N> + PrimInt,
+{
+    crate::coord_of(gray_encode(index))
+}
+
+/// Converts a Z-order curve index to the Gray-coded curve index of the same
+/// coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::gray;
+/// let idx = zorder::index_of([1u16, 1u16]);
+/// assert_eq!(gray::from_zorder::<_, 2>(idx), gray::index_of([1u16, 1u16]));
+/// ```
+#[inline]
+pub fn from_zorder<I, const N: usize>(index: I) -> I
+where
+    I: Deinterleave<N> + PrimInt,
+{
+    gray_decode(index)
+}
+
+/// Converts a Gray-coded curve index to the Z-order curve index of the same
+/// coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::gray;
+/// let idx = gray::index_of([1u16, 1u16]);
+/// assert_eq!(gray::to_zorder::<_, 2>(idx), zorder::index_of([1u16, 1u16]));
+/// ```
+#[inline]
+pub fn to_zorder<I, const N: usize>(index: I) -> I
+where
+    I: Deinterleave<N> + PrimInt,
+{
+    gray_encode(index)
+}
+
+/// `bmi2` module provides Gray-coded curve index and coordinate calculations
+/// using the bmi2 instruction set.
+///
+/// The functions mirror [`zorder::bmi2`](crate::bmi2) and have the same
+/// safety requirements.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{bmi2::HardwareSupportToken, gray};
+/// if let Some(support_token) = HardwareSupportToken::new() {
+///     let idx = gray::bmi2::index_of([1u16, 1u16], support_token);
+///     assert_eq!(idx, 2u32);
+/// }
+/// ```
+pub mod bmi2 {
+    use crate::{
+        bmi2::{DeinterleaveBMI2, HardwareSupportToken, InterleaveBMI2},
+        Deinterleave, Interleave,
+    };
+    use num_traits::PrimInt;
+
+    /// Safe wrapper around [`index_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn index_of<I, const N: usize>(
+        array: [I; N],
+        _support_token: HardwareSupportToken,
+    ) -> <I as Interleave<N>>::Output
+    where
+        I: InterleaveBMI2<N>,
+    {
+        // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+        // the `bmi2` instruction set is supported by the CPU and that it is safe
+        // to call `index_of_unchecked`.
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            index_of_unchecked(array)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            let _ = array;
+            unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+        }
+    }
+
+    /// Calculates Gray-coded curve index for given sequence of coordinates.
+    ///
+    /// This function requires the `bmi2` instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU. This can be checked at runtime:
+    ///
+    /// ```
+    /// if zorder::bmi2::has_hardware_support() {
+    ///     // ...
+    /// }
+    /// ```
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn index_of_unchecked<I, const N: usize>(
+        array: [I; N],
+    ) -> <I as Interleave<N>>::Output
+    where
+        I: InterleaveBMI2<N>,
+    {
+        super::gray_decode(crate::bmi2::index_of_unchecked(array))
+    }
+
+    /// Safe wrapper around [`coord_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn coord_of<I, const N: usize>(
+        index: I,
+        _support_token: HardwareSupportToken,
+    ) -> [<I as Deinterleave<N>>::Output; N]
+    where
+        I: DeinterleaveBMI2<N> + PrimInt,
+    {
+        // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+        // the `bmi2` instruction set is supported by the CPU and that it is safe
+        // to call `coord_of_unchecked`.
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            coord_of_unchecked(index)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            let _ = index;
+            unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+        }
+    }
+
+    /// Returns the N-dimensional coordinates of the given Gray-coded curve
+    /// index.
+    ///
+    /// This function requires the bmi2 instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU. This can be checked at runtime:
+    ///
+    /// ```
+    /// if zorder::bmi2::has_hardware_support() {
+    ///     // ...
+    /// }
+    /// ```
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn coord_of_unchecked<I, const N: usize>(
+        index: I,
+    ) -> [<I as Deinterleave<N>>::Output; N]
+    where
+        I: DeinterleaveBMI2<N> + PrimInt,
+    {
+        crate::bmi2::coord_of_unchecked(super::gray_encode(index))
+    }
+}
+
+/// Returns the Gray code of the given value.
+#[inline]
+fn gray_encode<I: PrimInt>(value: I) -> I {
+    value ^ value.unsigned_shr(1)
+}
+
+/// Returns the value whose Gray code is the given code. Each bit of the
+/// value is the parity of the bits at and above it in the code.
+#[inline]
+fn gray_decode<I: PrimInt>(code: I) -> I {
+    let bits = I::zero().count_zeros();
+    let mut value = code;
+    let mut shift = 1;
+    while shift < bits {
+        value = value ^ value.unsigned_shr(shift);
+        shift <<= 1;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_back() {
+        for i in 0..10_000u32 {
+            let array: [_; 2] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+
+        // `u128` holds only 72 bits of nine `u8` coordinates.
+        for i in (0..1u128 << 72).step_by(1 << 58) {
+            let array: [_; 9] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+    }
+
+    #[test]
+    fn consecutive_differ_in_one_bit() {
+        let mut prev: [u8; 3] = coord_of(0u32);
+
+        for i in 1..=0x00FF_FFFFu32 >> 8 {
+            let coord = coord_of(i);
+            let changed: u32 = prev
+                .iter()
+                .zip(coord.iter())
+                .map(|(a, b)| (a ^ b).count_ones())
+                .sum();
+            assert_eq!(changed, 1, "{prev:?} -> {coord:?} at {i}");
+            prev = coord;
+        }
+    }
+
+    #[test]
+    fn zorder_conversions() {
+        for i in (0..u64::MAX).step_by(1 << 48) {
+            let gray = from_zorder::<_, 2>(i);
+            assert_eq!(gray, index_of::<u32, 2>(crate::coord_of(i)));
+            assert_eq!(to_zorder::<_, 2>(gray), i);
+        }
+    }
+
+    #[test]
+    fn bmi2_matches_software() {
+        if let Some(support_token) = crate::bmi2::HardwareSupportToken::new() {
+            for i in 0..10_000u64 {
+                let array: [u16; 3] = coord_of(i);
+                assert_eq!(bmi2::coord_of::<_, 3>(i, support_token), array);
+                assert_eq!(bmi2::index_of(array, support_token), i);
+            }
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! This module provides functions to convert N-dimensional coordinates to
+//! [Hilbert curve](https://en.wikipedia.org/wiki/Hilbert_curve) indexes and back.
+//!
+//! Unlike the Z-order curve, consecutive Hilbert curve indexes are always
+//! neighbors in space, which makes range scans over boxes touch fewer
+//! disjoint index ranges.
+//!
+//! The conversions use the transpose algorithm from John Skilling,
+//! _Programming the Hilbert curve_ (2004). Coordinates are first transformed
+//! in place and then interleaved like Z-order curve indexes, so the output
+//! types are the same as with [`index_of`](crate::index_of), and the
+//! interleaving can be accelerated with the `bmi2` instruction set using the
+//! [`bmi2`](crate::hilbert::bmi2) submodule.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::hilbert::{coord_of, index_of};
+//!
+//! let idx = index_of([1u16, 1u16]);
+//! assert_eq!(idx, 2u32);
+//!
+//! let coord = coord_of(idx);
+//! assert_eq!(coord, [1u16, 1u16]);
+//! ```
+
+use num_traits::{PrimInt, Unsigned};
+
+use crate::{Deinterleave, Interleave};
+
+/// Calculates Hilbert curve index for given sequence of coordinates.
+///
+/// Output type will be the smallest unsigned integer type that can hold all
+/// of the given coordinates. Only unsigned coordinates are supported.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::hilbert::index_of;
+/// let idx = index_of([0u8, 1u8, 0u8]);
+/// assert_eq!(idx, 1u32);
+/// ```
+///
+/// ```compile_fail
+/// # use zorder::hilbert::index_of;
+/// let idx = index_of([1i8, 2i8]);
+/// ```
+#[inline]
+pub fn index_of<I, const N: usize>(array: [I; N]) -> <I as Interleave<N>>::Output
+where
+    I: Interleave<N> + PrimInt + Unsigned,
+{
+    crate::index_of(axes_to_transpose(array))
+}
+
+/// Returns the N-dimensional coordinates of the given Hilbert curve index.
+///
+/// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
+/// you may need to specify the number of dimensions `N` to disambiguate.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::hilbert::coord_of;
+/// let coord = coord_of(1u32);
+/// assert_eq!(coord, [0u8, 1u8, 0u8]);
+/// ```
+#[inline]
+pub fn coord_of<I, const N: usize>(index: I) -> [<I as Deinterleave<N>>::Output; N]
+where
+    I: Deinterleave<N> + Unsigned + Copy,
+{
+    transpose_to_axes(crate::coord_of(index))
+}
+
+/// Converts a Z-order curve index to the Hilbert curve index of the same
+/// coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::hilbert;
+/// let idx = zorder::index_of([1u16, 1u16]);
+/// assert_eq!(hilbert::from_zorder::<_, 2>(idx), hilbert::index_of([1u16, 1u16]));
+/// ```
+#[inline]
+pub fn from_zorder<I, const N: usize>(index: I) -> I
+where
+    I: Deinterleave<N> + Unsigned + Copy,
+    <I as Deinterleave<N>>::Output: Interleave<N, Output = I> + Unsigned,
+{
+    index_of::<_, N>(crate::coord_of(index))
+}
+
+/// Converts a Hilbert curve index to the Z-order curve index of the same
+/// coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::hilbert;
+/// let idx = hilbert::index_of([1u16, 1u16]);
+/// assert_eq!(hilbert::to_zorder::<_, 2>(idx), zorder::index_of([1u16, 1u16]));
+/// ```
+#[inline]
+pub fn to_zorder<I, const N: usize>(index: I) -> I
+where
+    I: Deinterleave<N> + Unsigned + Copy,
+    <I as Deinterleave<N>>::Output: Interleave<N, Output = I> + Unsigned,
+{
+    crate::index_of::<_, N>(coord_of(index))
+}
+
+/// `bmi2` module provides Hilbert curve index and coordinate calculations
+/// using the bmi2 instruction set.
+///
+/// The functions mirror [`zorder::bmi2`](crate::bmi2) and have the same
+/// safety requirements.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{bmi2::HardwareSupportToken, hilbert};
+/// if let Some(support_token) = HardwareSupportToken::new() {
+///     let idx = hilbert::bmi2::index_of([1u16, 1u16], support_token);
+///     assert_eq!(idx, 2u32);
+/// }
+/// ```
+pub mod bmi2 {
+    use crate::{
+        bmi2::{DeinterleaveBMI2, HardwareSupportToken, InterleaveBMI2},
+        Deinterleave, Interleave,
+    };
+    use num_traits::{PrimInt, Unsigned};
+
+    /// Safe wrapper around [`index_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn index_of<I, const N: usize>(
+        array: [I; N],
+        _support_token: HardwareSupportToken,
+    ) -> <I as Interleave<N>>::Output
+    where
+        I: InterleaveBMI2<N> + PrimInt + Unsigned,
+    {
+        // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+        // the `bmi2` instruction set is supported by the CPU and that it is safe
+        // to call `index_of_unchecked`.
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            index_of_unchecked(array)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            let _ = array;
+            unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+        }
+    }
+
+    /// Calculates Hilbert curve index for given sequence of coordinates.
+    ///
+    /// This function requires the `bmi2` instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU. This can be checked at runtime:
+    ///
+    /// ```
+    /// if zorder::bmi2::has_hardware_support() {
+    ///     // ...
+    /// }
+    /// ```
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn index_of_unchecked<I, const N: usize>(
+        array: [I; N],
+    ) -> <I as Interleave<N>>::Output
+    where
+        I: InterleaveBMI2<N> + PrimInt + Unsigned,
+    {
+        crate::bmi2::index_of_unchecked(super::axes_to_transpose(array))
+    }
+
+    /// Safe wrapper around [`coord_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn coord_of<I, const N: usize>(
+        index: I,
+        _support_token: HardwareSupportToken,
+    ) -> [<I as Deinterleave<N>>::Output; N]
+    where
+        I: DeinterleaveBMI2<N> + Unsigned + Copy,
+    {
+        // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+        // the `bmi2` instruction set is supported by the CPU and that it is safe
+        // to call `coord_of_unchecked`.
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            coord_of_unchecked(index)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            let _ = index;
+            unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+        }
+    }
+
+    /// Returns the N-dimensional coordinates of the given Hilbert curve index.
+    ///
+    /// This function requires the bmi2 instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU. This can be checked at runtime:
+    ///
+    /// ```
+    /// if zorder::bmi2::has_hardware_support() {
+    ///     // ...
+    /// }
+    /// ```
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn coord_of_unchecked<I, const N: usize>(
+        index: I,
+    ) -> [<I as Deinterleave<N>>::Output; N]
+    where
+        I: DeinterleaveBMI2<N> + Unsigned + Copy,
+    {
+        super::transpose_to_axes(crate::bmi2::coord_of_unchecked(index))
+    }
+}
+
+/// Transforms coordinates into the transposed form of the Hilbert curve
+/// index. The transposed form is returned in reverse order, so that the
+/// first axis holds the least significant bits of each group when
+/// interleaved.
+#[inline]
+fn axes_to_transpose<I, const N: usize>(mut x: [I; N]) -> [I; N]
+where
+    I: PrimInt,
+{
+    let bits = I::zero().count_zeros() as usize;
+
+    // Inverse undo.
+    for level in (1..bits).rev() {
+        let q = I::one() << level;
+        let p = q - I::one();
+
+        for i in 0..N {
+            if x[i] & q != I::zero() {
+                // Invert the low bits of the first axis.
+                x[0] = x[0] ^ p;
+            } else {
+                // Exchange the low bits of the first and the current axis.
+                let t = (x[0] ^ x[i]) & p;
+                x[0] = x[0] ^ t;
+                x[i] = x[i] ^ t;
+            }
+        }
+    }
+
+    // Gray encode.
+    for i in 1..N {
+        x[i] = x[i] ^ x[i - 1];
+    }
+
+    let mut t = I::zero();
+    for level in (1..bits).rev() {
+        let q = I::one() << level;
+        if x[N - 1] & q != I::zero() {
+            t = t ^ (q - I::one());
+        }
+    }
+
+    for value in x.iter_mut() {
+        *value = *value ^ t;
+    }
+
+    x.reverse();
+    x
+}
+
+/// Transforms the transposed form of the Hilbert curve index back into
+/// coordinates. Inverse of [`axes_to_transpose`].
+#[inline]
+fn transpose_to_axes<I, const N: usize>(mut x: [I; N]) -> [I; N]
+where
+    I: PrimInt,
+{
+    let bits = I::zero().count_zeros() as usize;
+    x.reverse();
+
+    // Gray decode.
+    let t = x[N - 1].unsigned_shr(1);
+    for i in (1..N).rev() {
+        x[i] = x[i] ^ x[i - 1];
+    }
+    x[0] = x[0] ^ t;
+
+    // Undo excess work.
+    for level in 1..bits {
+        let q = I::one() << level;
+        let p = q - I::one();
+
+        for i in (0..N).rev() {
+            if x[i] & q != I::zero() {
+                x[0] = x[0] ^ p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] = x[0] ^ t;
+                x[i] = x[i] ^ t;
+            }
+        }
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns true if the coordinates differ by one along exactly one axis.
+    fn adjacent<const N: usize>(a: [u8; N], b: [u8; N]) -> bool {
+        let diff: u32 = a
+            .iter()
+            .zip(b.iter())
+            .map(|(&a, &b)| a.abs_diff(b) as u32)
+            .sum();
+        diff == 1
+    }
+
+    #[test]
+    fn index_and_back() {
+        for i in 0..10_000u32 {
+            let array: [_; 2] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+
+        for i in 0..10_000u64 {
+            let array: [_; 3] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+
+        for i in 0..10_000u128 {
+            let array: [_; 9] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+    }
+
+    #[test]
+    fn continuous_2d() {
+        let mut prev: [u8; 2] = coord_of(0u16);
+        assert_eq!(prev, [0, 0]);
+
+        for i in 1..=u16::MAX {
+            let coord = coord_of(i);
+            assert!(adjacent(prev, coord), "{prev:?} -> {coord:?} at {i}");
+            prev = coord;
+        }
+    }
+
+    #[test]
+    fn continuous_3d() {
+        let mut prev: [u8; 3] = coord_of(0u32);
+
+        for i in 1..=0x00FF_FFFFu32 >> 6 {
+            let coord = coord_of(i);
+            assert!(adjacent(prev, coord), "{prev:?} -> {coord:?} at {i}");
+            prev = coord;
+        }
+    }
+
+    #[test]
+    fn zorder_conversions() {
+        for i in 0..10_000u64 {
+            let hilbert = from_zorder::<_, 2>(i);
+            assert_eq!(hilbert, index_of::<u32, 2>(crate::coord_of(i)));
+            assert_eq!(to_zorder::<_, 2>(hilbert), i);
+        }
+    }
+
+    #[test]
+    fn bmi2_matches_software() {
+        if let Some(support_token) = crate::bmi2::HardwareSupportToken::new() {
+            for i in 0..10_000u64 {
+                let array: [u16; 3] = coord_of(i);
+                assert_eq!(bmi2::coord_of::<_, 3>(i, support_token), array);
+                assert_eq!(bmi2::index_of(array, support_token), i);
+            }
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use num_traits::{cast::AsPrimitive, PrimInt};
 
-use crate::mask::{interleave_mask, interleave_shift, BitCount};
+use crate::{
+    float::TotalOrder,
+    lane::Lane,
+    mask::{const_interleave_mask, const_steps, BitCount},
+    sign::{fills_index, sign_bit, Reinterpret},
+};
 
 /// Interleaves the bits of the given number, while taking output dimension
 /// into account.
@@ -9,9 +14,13 @@ use crate::mask::{interleave_mask, interleave_shift, BitCount};
 /// number of bits in the number. Implementations seen here are O(log n).
 /// They are extrapolated and generalized from the algorithm described here:
 /// <http://graphics.stanford.edu/~seander/bithacks.html#InterleaveBMN>.
+///
+/// This trait cannot be implemented outside of this crate. Implement
+/// [`Lane`](crate::lane::Lane) instead to interleave your own types.
 pub trait Interleave<const N: usize>: private::Sealed {
-    /// Smallest unsigned integer type that can hold the interleaved bits.
-    type Output: PrimInt;
+    /// Smallest integer type that can hold the interleaved bits. Signed
+    /// numbers are interleaved into signed integers.
+    type Output: BitCount + PrimInt;
 
     /// Interleaves the bits of the given number.
     ///
@@ -19,35 +28,143 @@ pub trait Interleave<const N: usize>: private::Sealed {
     /// used bits, so that all numbers can be interleaved without
     /// overlapping.
     fn interleave(self) -> Self::Output;
+
+    /// Converts the combined interleaved bits of all `N` numbers into the
+    /// final index.
+    #[doc(hidden)]
+    #[inline]
+    fn finish(index: Self::Output) -> Self::Output {
+        index
+    }
+}
+
+/// Implements [`Interleave`] for unsigned numbers using the steps of
+/// [`InterleaveOutput`].
+macro_rules! impl_interleave_unsigned {
+    ($($impl_type:ty),*) => {
+        $(
+            impl<const N: usize> Interleave<N> for $impl_type
+            where
+                $impl_type: InterleaveOutput<N>,
+                $impl_type: AsPrimitive<<Self as InterleaveOutput<N>>::Output>,
+            {
+                type Output = <Self as InterleaveOutput<N>>::Output;
+
+                #[inline]
+                fn interleave(self) -> Self::Output {
+                    let mut x = self.as_();
+
+                    for &(mask, shift_count) in <Self as InterleaveOutput<N>>::STEPS {
+                        x = (x | x.unsigned_shl(shift_count)) & mask;
+                    }
+
+                    x
+                }
+            }
+        )*
+    };
+}
+
+impl_interleave_unsigned!(u8, u16, u32, u64);
+
+/// Implements [`Interleave`] for signed numbers by flipping their sign bit,
+/// which maps them in order to the unsigned numbers of the same width.
+///
+/// The index is flipped back to signed order if the most significant bit of
+/// the index is the sign bit of the last number, so that sorting the signed
+/// indexes still follows the Z-order curve.
+macro_rules! impl_interleave_signed {
+    ($($impl_type:ty => $unsigned:ty);*) => {
+        $(
+            impl<const N: usize> Interleave<N> for $impl_type
+            where
+                $unsigned: Interleave<N>,
+                <$unsigned as Interleave<N>>::Output: Reinterpret,
+            {
+                type Output = <<$unsigned as Interleave<N>>::Output as Reinterpret>::Other;
+
+                #[inline]
+                fn interleave(self) -> Self::Output {
+                    let unsigned = self.reinterpret() ^ sign_bit::<$unsigned>();
+                    <$unsigned as Interleave<N>>::interleave(unsigned).reinterpret()
+                }
+
+                #[inline]
+                fn finish(index: Self::Output) -> Self::Output {
+                    if fills_index::<Self, Self::Output>(N) {
+                        index ^ sign_bit::<Self::Output>()
+                    } else {
+                        index
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_interleave_signed! {
+    i8 => u8;
+    i16 => u16;
+    i32 => u32;
+    i64 => u64
+}
+
+/// Implements [`Interleave`] for floating-point numbers by interleaving
+/// their bits mapped with [`TotalOrder`].
+macro_rules! impl_interleave_float {
+    ($($impl_type:ty => $bits:ty);*) => {
+        $(
+            impl<const N: usize> Interleave<N> for $impl_type
+            where
+                $bits: Interleave<N>,
+            {
+                type Output = <$bits as Interleave<N>>::Output;
+
+                #[inline]
+                fn interleave(self) -> Self::Output {
+                    <$bits as Interleave<N>>::interleave(self.to_ordered_bits())
+                }
+            }
+        )*
+    };
 }
 
+impl_interleave_float! {
+    f32 => u32;
+    f64 => u64
+}
+
+/// Implements [`Interleave`] for [`Lane`] types by interleaving their bits
+/// in their place.
 impl<T, const N: usize> Interleave<N> for T
 where
-    T: InterleaveOutput<N>,
-    T: AsPrimitive<<Self as InterleaveOutput<N>>::Output>,
-    T: BitCount + PrimInt,
+    T: Lane,
+    <T as Lane>::Bits: Interleave<N>,
 {
-    type Output = <Self as InterleaveOutput<N>>::Output;
+    type Output = <<T as Lane>::Bits as Interleave<N>>::Output;
 
     #[inline]
     fn interleave(self) -> Self::Output {
-        let mut x = self.as_();
-
-        for i in (0..<Self as BitCount>::BITS_ILOG2).rev() {
-            let mask = interleave_mask(N as u32, 1 << i);
-            let shift_count = interleave_shift(N as u32, i);
-
-            x = (x | x.unsigned_shl(shift_count)) & mask;
-        }
+        <T as Lane>::Bits::interleave(self.to_bits())
+    }
 
-        x
+    #[inline]
+    fn finish(index: Self::Output) -> Self::Output {
+        <<T as Lane>::Bits as Interleave<N>>::finish(index)
     }
 }
 
 /// Used to determine the minimum width output type which
 /// fits the given input type `N` (dimensions) number of times.
+///
+/// Masks and shift amounts of each interleaving step are computed at compile
+/// time for every type and dimension combination.
 pub trait InterleaveOutput<const N: usize>: private::Sealed {
-    type Output: BitCount + PrimInt;
+    type Output: BitCount + PrimInt + 'static;
+
+    /// Mask and shift amount of each interleaving step in the order they are
+    /// applied.
+    const STEPS: &'static [(Self::Output, u32)];
 }
 
 macro_rules! impl_interleave_output {
@@ -55,6 +172,9 @@ macro_rules! impl_interleave_output {
         $(
             impl InterleaveOutput<$dim> for $impl_type {
                 type Output = $out_type;
+
+                const STEPS: &'static [(Self::Output, u32)] =
+                    &const_steps!(interleave_steps, $dim, $impl_type, $out_type);
             }
         )*
     };
@@ -108,7 +228,8 @@ macro_rules! impl_interleave_bmi2_32 {
                 unsafe fn interleave_bmi2(self) -> <Self as Interleave<$dim>>::Output {
                     #[cfg(target_arch = "x86_64")]
                     {
-                        core::arch::x86_64::_pdep_u32(self.as_(), interleave_mask($dim, 1)).as_()
+                        const MASK: u32 = const_interleave_mask($dim, 1, u32::BITS) as u32;
+                        core::arch::x86_64::_pdep_u32(self.as_(), MASK).as_()
                     }
                     #[cfg(not(target_arch = "x86_64"))]
                     panic!("BMI2 feature is not supported on this architecture")
@@ -126,7 +247,38 @@ macro_rules! impl_interleave_bmi2_64 {
                 unsafe fn interleave_bmi2(self) -> <Self as Interleave<$dim>>::Output {
                     #[cfg(target_arch = "x86_64")]
                     {
-                        core::arch::x86_64::_pdep_u64(self.as_(), interleave_mask($dim, 1)).as_()
+                        const MASK: u64 = const_interleave_mask($dim, 1, u64::BITS) as u64;
+                        core::arch::x86_64::_pdep_u64(self.as_(), MASK).as_()
+                    }
+                    #[cfg(not(target_arch = "x86_64"))]
+                    panic!("BMI2 feature is not supported on this architecture")
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [`InterleaveBMI2`] for `u128` outputs by depositing the low
+/// and high halves of the index separately. The high half receives the bits
+/// which did not fit into the low half.
+macro_rules! impl_interleave_bmi2_128 {
+    ($($dim:expr, $impl_type:ty);*) => {
+        $(
+            impl InterleaveBMI2<$dim> for $impl_type {
+                #[inline]
+                unsafe fn interleave_bmi2(self) -> <Self as Interleave<$dim>>::Output {
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        use core::arch::x86_64::_pdep_u64;
+
+                        const MASK: u128 = const_interleave_mask($dim, 1, u128::BITS);
+                        const LO_MASK: u64 = MASK as u64;
+                        const HI_MASK: u64 = (MASK >> 64) as u64;
+
+                        let x: u64 = self.as_();
+                        let lo = _pdep_u64(x, LO_MASK);
+                        let hi = _pdep_u64(x >> LO_MASK.count_ones(), HI_MASK);
+                        (hi as u128) << 64 | lo as u128
                     }
                     #[cfg(not(target_arch = "x86_64"))]
                     panic!("BMI2 feature is not supported on this architecture")
@@ -136,6 +288,51 @@ macro_rules! impl_interleave_bmi2_64 {
     };
 }
 
+macro_rules! impl_interleave_bmi2_signed {
+    ($($impl_type:ty => $unsigned:ty);*) => {
+        $(
+            impl<const N: usize> InterleaveBMI2<N> for $impl_type
+            where
+                $unsigned: InterleaveBMI2<N>,
+                <$unsigned as Interleave<N>>::Output: Reinterpret,
+            {
+                #[inline]
+                unsafe fn interleave_bmi2(self) -> <Self as Interleave<N>>::Output {
+                    let unsigned = self.reinterpret() ^ sign_bit::<$unsigned>();
+                    <$unsigned as InterleaveBMI2<N>>::interleave_bmi2(unsigned).reinterpret()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_interleave_bmi2_float {
+    ($($impl_type:ty => $bits:ty);*) => {
+        $(
+            impl<const N: usize> InterleaveBMI2<N> for $impl_type
+            where
+                $bits: InterleaveBMI2<N>,
+            {
+                #[inline]
+                unsafe fn interleave_bmi2(self) -> <Self as Interleave<N>>::Output {
+                    <$bits as InterleaveBMI2<N>>::interleave_bmi2(self.to_ordered_bits())
+                }
+            }
+        )*
+    };
+}
+
+impl<T, const N: usize> InterleaveBMI2<N> for T
+where
+    T: Lane,
+    <T as Lane>::Bits: InterleaveBMI2<N>,
+{
+    #[inline]
+    unsafe fn interleave_bmi2(self) -> <Self as Interleave<N>>::Output {
+        <T as Lane>::Bits::interleave_bmi2(self.to_bits())
+    }
+}
+
 impl_interleave_bmi2_32! {
     2, u8;
     3, u8;
@@ -153,13 +350,53 @@ impl_interleave_bmi2_64! {
     2, u32
 }
 
+impl_interleave_bmi2_128! {
+    9, u8;
+    10, u8;
+    11, u8;
+    12, u8;
+    13, u8;
+    14, u8;
+    15, u8;
+    16, u8;
+    5, u16;
+    6, u16;
+    7, u16;
+    8, u16;
+    3, u32;
+    4, u32;
+    2, u64
+}
+
+impl_interleave_bmi2_signed! {
+    i8 => u8;
+    i16 => u16;
+    i32 => u32;
+    i64 => u64
+}
+
+impl_interleave_bmi2_float! {
+    f32 => u32;
+    f64 => u64
+}
+
 mod private {
+    use crate::lane::Lane;
+
     pub trait Sealed {}
 
+    impl<T: Lane> Sealed for T {}
+
     impl Sealed for u8 {}
     impl Sealed for u16 {}
     impl Sealed for u32 {}
     impl Sealed for u64 {}
+    impl Sealed for i8 {}
+    impl Sealed for i16 {}
+    impl Sealed for i32 {}
+    impl Sealed for i64 {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
 }
 
 #[cfg(test)]
@@ -196,6 +433,19 @@ mod tests {
         assert_eq!(idx, 0x5555_5555_5555_5555_5555_5555_5555_5555);
     }
 
+    #[test]
+    fn interleave_dim2_i8() {
+        assert_eq!(<i8 as Interleave<2>>::interleave(i8::MIN), 0);
+        assert_eq!(
+            <i8 as Interleave<2>>::interleave(0),
+            0b01_00_00_00_00_00_00_00
+        );
+        assert_eq!(
+            <i8 as Interleave<2>>::interleave(-1),
+            0b00_01_01_01_01_01_01_01
+        );
+    }
+
     #[test]
     fn odd_large_dimension_interleave() {
         let idx = <u8 as Interleave<13>>::interleave(u8::MAX);
@@ -0,0 +1,324 @@
+//! This module provides support for user-defined coordinate types.
+//!
+//! Types which implement [`Lane`] can be passed directly to
+//! [`index_of`](crate::index_of), [`bmi2::index_of`](crate::bmi2::index_of)
+//! and [`dispatch::index_of`](crate::dispatch::index_of), and they are
+//! interleaved like their [`Lane::Bits`]. Since the output index types
+//! cannot tell apart the user-defined and the underlying coordinates,
+//! decoding is done with [`coord_of`] of this module.
+//!
+//! [`Lane`] is implemented for [`Wrapping`] and the non-zero integer types.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::{index_of, lane::{coord_of, Lane}};
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq)]
+//! struct TileX(u32);
+//!
+//! impl Lane for TileX {
+//!     type Bits = u32;
+//!
+//!     fn to_bits(self) -> u32 {
+//!         self.0
+//!     }
+//!
+//!     fn from_bits(bits: u32) -> Self {
+//!         TileX(bits)
+//!     }
+//! }
+//!
+//! let idx = index_of([TileX(3), TileX(7)]);
+//! assert_eq!(idx, 0b101_111u64);
+//!
+//! let coord: [TileX; 2] = coord_of(idx);
+//! assert_eq!(coord, [TileX(3), TileX(7)]);
+//! ```
+
+use core::{
+    num::{NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8},
+    num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, Wrapping},
+};
+
+use crate::Deinterleave;
+
+/// Maps a type to and from a number which is interleaved in its place.
+///
+/// `Bits` is usually an unsigned integer type. Signed integers and floats
+/// are also interleaved in order as described in [`index_of`](crate::index_of),
+/// but only integers can be decoded with [`coord_of`].
+pub trait Lane: Copy {
+    /// Number type which is interleaved in place of `Self`.
+    type Bits: Copy;
+
+    /// Maps the value to the number which is interleaved.
+    fn to_bits(self) -> Self::Bits;
+
+    /// Inverse of [`Lane::to_bits`].
+    fn from_bits(bits: Self::Bits) -> Self;
+}
+
+impl<T: Copy> Lane for Wrapping<T> {
+    type Bits = T;
+
+    #[inline]
+    fn to_bits(self) -> Self::Bits {
+        self.0
+    }
+
+    #[inline]
+    fn from_bits(bits: Self::Bits) -> Self {
+        Wrapping(bits)
+    }
+}
+
+/// Implements [`Lane`] for non-zero integers. Decoding panics if the
+/// coordinate is zero, which is only possible for indexes which were not
+/// calculated from non-zero coordinates.
+macro_rules! impl_lane_non_zero {
+    ($($impl_type:ty => $bits:ty);*) => {
+        $(
+            impl Lane for $impl_type {
+                type Bits = $bits;
+
+                #[inline]
+                fn to_bits(self) -> Self::Bits {
+                    self.get()
+                }
+
+                #[inline]
+                fn from_bits(bits: Self::Bits) -> Self {
+                    <$impl_type>::new(bits).expect("zero coordinate for a non-zero type")
+                }
+            }
+        )*
+    };
+}
+
+impl_lane_non_zero! {
+    NonZeroU8 => u8;
+    NonZeroU16 => u16;
+    NonZeroU32 => u32;
+    NonZeroU64 => u64;
+    NonZeroI8 => i8;
+    NonZeroI16 => i16;
+    NonZeroI32 => i32;
+    NonZeroI64 => i64
+}
+
+/// Returns the N-dimensional coordinates of type `L` of the given Z-order
+/// curve index.
+///
+/// The coordinate type `L` usually needs to be specified, since the same
+/// index types are used for the underlying coordinates.
+///
+/// # Panics
+///
+/// If [`Lane::from_bits`] panics, such as for a zero coordinate of a
+/// non-zero integer type.
+///
+/// # Examples
+///
+/// ```
+/// # use core::num::Wrapping;
+/// # use zorder::{index_of, lane::coord_of};
+/// let idx = index_of([Wrapping(-3i16), Wrapping(7i16)]);
+/// let coord: [Wrapping<i16>; 2] = coord_of(idx);
+/// assert_eq!(coord, [Wrapping(-3i16), Wrapping(7i16)]);
+/// ```
+#[inline]
+pub fn coord_of<L, I, const N: usize>(index: I) -> [L; N]
+where
+    L: Lane,
+    I: Deinterleave<N, Output = <L as Lane>::Bits> + Copy,
+{
+    crate::coord_of(index).map(L::from_bits)
+}
+
+/// `bmi2` module provides user-defined coordinate calculations using the
+/// bmi2 instruction set.
+///
+/// The functions mirror [`zorder::bmi2`](crate::bmi2) and have the same
+/// safety requirements.
+///
+/// # Examples
+///
+/// ```
+/// # use core::num::NonZeroU16;
+/// # use zorder::{bmi2::HardwareSupportToken, lane};
+/// if let Some(support_token) = HardwareSupportToken::new() {
+///     let x = NonZeroU16::new(3).unwrap();
+///     let y = NonZeroU16::new(7).unwrap();
+///     let idx = zorder::bmi2::index_of([x, y], support_token);
+///     let coord: [NonZeroU16; 2] = lane::bmi2::coord_of(idx, support_token);
+///     assert_eq!(coord, [x, y]);
+/// }
+/// ```
+pub mod bmi2 {
+    use super::Lane;
+    use crate::bmi2::{DeinterleaveBMI2, HardwareSupportToken};
+    use crate::Deinterleave;
+
+    /// Safe wrapper around [`coord_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn coord_of<L, I, const N: usize>(index: I, support_token: HardwareSupportToken) -> [L; N]
+    where
+        L: Lane,
+        I: DeinterleaveBMI2<N> + Deinterleave<N, Output = <L as Lane>::Bits> + Copy,
+    {
+        crate::bmi2::coord_of(index, support_token).map(L::from_bits)
+    }
+
+    /// Returns the N-dimensional coordinates of type `L` of the given
+    /// Z-order curve index.
+    ///
+    /// This function requires the `bmi2` instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU.
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn coord_of_unchecked<L, I, const N: usize>(index: I) -> [L; N]
+    where
+        L: Lane,
+        I: DeinterleaveBMI2<N> + Deinterleave<N, Output = <L as Lane>::Bits> + Copy,
+    {
+        crate::bmi2::coord_of_unchecked(index).map(L::from_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bmi2::HardwareSupportToken, index_of};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    struct TileX(u32);
+
+    impl Lane for TileX {
+        type Bits = u32;
+
+        fn to_bits(self) -> u32 {
+            self.0
+        }
+
+        fn from_bits(bits: u32) -> Self {
+            TileX(bits)
+        }
+    }
+
+    #[test]
+    fn newtype_index_and_back() {
+        for i in 0..10_000u64 {
+            let coord: [u32; 2] = crate::coord_of(i);
+            let tiles = coord.map(TileX);
+            assert_eq!(index_of(tiles), i);
+            assert_eq!(coord_of::<TileX, _, 2>(i), tiles);
+            assert_eq!(crate::dispatch::index_of(tiles), i);
+        }
+    }
+
+    /// Lane type whose size is not a power of two.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Tagged {
+        x: u32,
+        tag: [u32; 2],
+    }
+
+    impl Lane for Tagged {
+        type Bits = u32;
+
+        fn to_bits(self) -> u32 {
+            self.x
+        }
+
+        fn from_bits(bits: u32) -> Self {
+            Tagged {
+                x: bits,
+                tag: [0; 2],
+            }
+        }
+    }
+
+    /// Zero-sized lane type.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Origin;
+
+    impl Lane for Origin {
+        type Bits = u8;
+
+        fn to_bits(self) -> u8 {
+            0
+        }
+
+        fn from_bits(_: u8) -> Self {
+            Origin
+        }
+    }
+
+    #[test]
+    fn dispatch_any_lane_size() {
+        use crate::dispatch;
+
+        let tagged = [3, 7, 1].map(|x| Tagged { x, tag: [1; 2] });
+        assert_eq!(dispatch::index_of(tagged), index_of([3u32, 7, 1]));
+        assert_eq!(
+            dispatch::index_of_backend::<Tagged, 3>(),
+            dispatch::index_of_backend::<u32, 3>()
+        );
+
+        assert_eq!(dispatch::index_of([Origin; 16]), 0u128);
+        assert_eq!(
+            dispatch::index_of_backend::<Origin, 16>(),
+            dispatch::index_of_backend::<u8, 16>()
+        );
+    }
+
+    #[test]
+    fn wrapping_signed_order() {
+        for x in i8::MIN..=i8::MAX {
+            let a = [Wrapping(x), Wrapping(x / -2)];
+            let b = [Wrapping(x / 3), Wrapping(x)];
+            assert_eq!(index_of(a), index_of(a.map(|w| w.0)));
+            assert_eq!(coord_of::<Wrapping<i8>, _, 2>(index_of(a)), a);
+            assert_eq!(
+                index_of(a).cmp(&index_of(b)),
+                index_of(a.map(|w| w.0)).cmp(&index_of(b.map(|w| w.0)))
+            );
+        }
+    }
+
+    #[test]
+    fn non_zero_index_and_back() {
+        let coord = [3u16, 7, 1].map(|x| NonZeroU16::new(x).unwrap());
+        let idx = index_of(coord);
+        assert_eq!(idx, index_of([3u16, 7, 1]));
+        assert_eq!(coord_of::<NonZeroU16, _, 3>(idx), coord);
+    }
+
+    #[test]
+    #[should_panic(expected = "zero coordinate")]
+    fn non_zero_decodes_zero() {
+        coord_of::<NonZeroU32, _, 2>(0u64);
+    }
+
+    #[test]
+    fn bmi2_matches_software() {
+        let Some(support_token) = HardwareSupportToken::new() else {
+            return;
+        };
+
+        for i in 0..10_000u64 {
+            let tiles = coord_of::<TileX, _, 2>(i);
+            assert_eq!(crate::bmi2::index_of(tiles, support_token), i);
+            assert_eq!(bmi2::coord_of::<TileX, _, 2>(i, support_token), tiles);
+        }
+    }
+}
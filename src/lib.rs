@@ -8,7 +8,37 @@
 //! implementation supported by all platforms and one using bmi2 instructions
 //! supported by modern x86_64 CPUs.
 //!
-//! [^1]: Maximum number of dimensions is limited by the largest unsigned integer type, `u128`, which is able to store 16 8-bit coordinates. `bmi2` based approach is limited to `u64`.
+//! The [`avx2`] module converts batches of coordinates and indexes at once
+//! using the AVX2 instruction set, and the [`clmul`] module accelerates
+//! 2-dimensional indexes using carry-less multiplication.
+//!
+//! The [`dispatch`] module picks the fastest of the two implementations at
+//! runtime, without tokens or unsafe.
+//!
+//! The [`const_fn`] module provides the conversions as `const fn`.
+//!
+//! The [`lane`] module lets your own coordinate types, such as newtypes
+//! around integers, be converted like the integers.
+//!
+//! [`cmp_zorder`] and [`ZOrdered`] compare coordinates in Z-order without
+//! calculating their indexes.
+//!
+//! [`MortonIndex`] is an index which records its coordinate type and
+//! dimension in its type.
+//!
+//! [`index_of_as`] and [`coord_of_as`] use an index type wider than the
+//! smallest one, so that indexes of different coordinate types can share a
+//! type.
+//!
+//! [`try_index_of`] and [`try_coord_of`] validate coordinates and indexes
+//! from untrusted sources instead of silently truncating them.
+//!
+//! The [`hilbert`], [`peano`] and [`gray`] modules provide the same
+//! conversions for the Hilbert, Peano and Gray-coded Z-order curves, and
+//! [`curve::SpaceFillingCurve`] abstracts over all of them except the Peano
+//! curve, whose conversions are fallible.
+//!
+//! [^1]: Maximum number of dimensions is limited by the largest unsigned integer type, `u128`, which is able to store 16 8-bit coordinates. `bmi2` based approach converts `u128` indexes in two 64-bit halves.
 //!
 //! # Examples
 //!
@@ -55,17 +85,44 @@
 #[cfg(feature = "std")]
 extern crate std;
 
+pub mod avx2;
+pub mod clmul;
+pub mod const_fn;
+pub mod curve;
 mod deinterleave;
+pub mod dilated;
+pub mod dispatch;
+mod error;
+pub mod float;
+pub mod gray;
+pub mod hilbert;
 mod interleave;
+pub mod lane;
+#[cfg(feature = "lut")]
+pub mod lut;
 mod mask;
+mod morton;
+pub mod neighbors;
+mod order;
+pub mod peano;
+pub mod query;
+mod sign;
 
 pub use deinterleave::Deinterleave;
+pub use error::Error;
 pub use interleave::Interleave;
+pub use morton::MortonIndex;
+pub use order::{cmp_zorder, ZOrdered};
+
+use mask::BitCount;
+use num_traits::{AsPrimitive, PrimInt};
 
 /// Calculates Z-order curve index for given sequence of coordinates.
 ///
-/// Output type will be the smallest unsigned integer type that can hold all
-/// of the given coordinates.
+/// Output type will be the smallest integer type that can hold all of the
+/// given coordinates. Signed coordinates produce signed indexes, which
+/// sort in Z-order over the whole signed domain. Floating-point coordinates
+/// are supported as described in the [`float`] module.
 ///
 /// # Examples
 ///
@@ -73,6 +130,8 @@ pub use interleave::Interleave;
 /// # use zorder::index_of;
 /// let idx = index_of([3u32, 7u32]);
 /// assert_eq!(idx, 0b101_111u64);
+///
+/// assert!(index_of([-1i32, -1i32]) < index_of([0i32, 0i32]));
 /// ```
 #[inline]
 pub fn index_of<I, const N: usize>(array: [I; N]) -> <I as Interleave<N>>::Output
@@ -82,6 +141,68 @@ where
     util::generic_index_of(array, Interleave::interleave)
 }
 
+/// Calculates Z-order curve index for given sequence of coordinates as type
+/// `O`, which must be at least as wide as the output of [`index_of`].
+///
+/// This allows indexes of coordinates of different widths to share a type.
+/// Widening preserves the Z-order, also for signed indexes. Types which are
+/// too narrow for the index do not compile.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::index_of_as;
+/// let idx = index_of_as::<u64, _, 2>([3u16, 7u16]);
+/// assert_eq!(idx, 0b101_111u64);
+///
+/// assert!(index_of_as::<i64, _, 2>([-1i16, -1i16]) < index_of_as::<i64, _, 2>([0i16, 0i16]));
+/// ```
+///
+/// ```compile_fail
+/// # use zorder::index_of_as;
+/// let idx = index_of_as::<u16, _, 2>([3u16, 7u16]);
+/// ```
+#[inline]
+pub fn index_of_as<O, I, const N: usize>(array: [I; N]) -> O
+where
+    I: Interleave<N>,
+    <I as Interleave<N>>::Output: Into<O>,
+{
+    index_of(array).into()
+}
+
+/// Returns the N-dimensional coordinates of type `T` of the given Z-order
+/// curve index, which was calculated by [`index_of_as`] or widened from the
+/// output of [`index_of`].
+///
+/// Only the bits of the index which are used by `[T; N]` are decoded. Types
+/// which are too narrow for the index of `[T; N]` do not compile.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{coord_of_as, index_of_as};
+/// let idx = index_of_as::<u64, _, 2>([3u16, 7u16]);
+/// assert_eq!(coord_of_as::<u16, _, 2>(idx), [3u16, 7u16]);
+///
+/// let idx = index_of_as::<i64, _, 2>([-3i8, 7i8]);
+/// assert_eq!(coord_of_as::<i8, _, 2>(idx), [-3i8, 7i8]);
+/// ```
+///
+/// ```compile_fail
+/// # use zorder::coord_of_as;
+/// let coord = coord_of_as::<u16, _, 2>(0u16);
+/// ```
+#[inline]
+pub fn coord_of_as<T, O, const N: usize>(index: O) -> [T; N]
+where
+    T: Interleave<N>,
+    <T as Interleave<N>>::Output: Deinterleave<N, Output = T> + Into<O> + 'static,
+    O: AsPrimitive<<T as Interleave<N>>::Output>,
+{
+    coord_of(index.as_())
+}
+
 /// Returns the N-dimensional coordinates of the given Z-order curve index.
 ///
 /// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
@@ -90,16 +211,229 @@ where
 /// # Examples
 ///
 /// ```
-/// # use zorder::coord_of;
+/// # use zorder::{coord_of, index_of};
 /// let coord = coord_of(0b101_111u64);
 /// assert_eq!(coord, [3u32, 7u32]);
+///
+/// let coord = coord_of(index_of([-3i32, 7i32]));
+/// assert_eq!(coord, [-3i32, 7i32]);
 /// ```
 #[inline]
 pub fn coord_of<I, const N: usize>(index: I) -> [<I as Deinterleave<N>>::Output; N]
 where
     I: Deinterleave<N> + Copy,
 {
-    util::generic_coord_of(index, Deinterleave::deinterleave)
+    index.deinterleave_all()
+}
+
+/// Returns a single coordinate of the given Z-order curve index without
+/// decoding the other coordinates.
+///
+/// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
+/// you may need to specify the number of dimensions `N` to disambiguate.
+///
+/// # Panics
+///
+/// If `axis` is not less than `N`.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{axis_of, index_of};
+/// let idx = index_of([3u16, 7u16, 5u16]);
+/// assert_eq!(axis_of::<_, 3>(idx, 1), 7u16);
+/// ```
+#[inline]
+pub fn axis_of<I, const N: usize>(index: I, axis: usize) -> <I as Deinterleave<N>>::Output
+where
+    I: Deinterleave<N>,
+{
+    assert!(axis < N, "axis {axis} out of range for {N} dimensions");
+    index.deinterleave(axis)
+}
+
+/// Returns the given Z-order curve index with a single coordinate replaced
+/// by `value`. The other coordinates are kept as they are.
+///
+/// # Panics
+///
+/// If `axis` is not less than `N`.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{index_of, with_axis};
+/// let idx = index_of([3u16, 7u16, 5u16]);
+/// assert_eq!(with_axis::<_, 3>(idx, 2, 1u16), index_of([3u16, 7u16, 1u16]));
+/// ```
+#[inline]
+pub fn with_axis<I, const N: usize>(
+    index: I,
+    axis: usize,
+    value: <I as Deinterleave<N>>::Output,
+) -> I
+where
+    I: Deinterleave<N> + PrimInt,
+    <I as Deinterleave<N>>::Output: Interleave<N, Output = I>,
+{
+    util::generic_with_axis(
+        index,
+        axis,
+        value,
+        Deinterleave::deinterleave,
+        Interleave::interleave,
+    )
+}
+
+/// Calculates Z-order curve index for given sequence of coordinates, if each
+/// coordinate fits into `bits` bits.
+///
+/// Unsigned coordinates fit if they are less than `2^bits`, and signed
+/// coordinates fit if they are in the range of a `bits` wide signed integer.
+/// Dimensions which are not supported by the coordinate type do not compile.
+///
+/// # Errors
+///
+/// Returns [`Error::BitsOutOfRange`] if `bits` is larger than the coordinate
+/// type, or [`Error::CoordinateOutOfRange`] if any coordinate does not fit
+/// into `bits` bits.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{try_index_of, Error};
+/// assert_eq!(try_index_of([3u32, 7u32], 3), Ok(0b101_111u64));
+/// assert_eq!(
+///     try_index_of([3u32, 8u32], 3),
+///     Err(Error::CoordinateOutOfRange { axis: 1 })
+/// );
+/// ```
+#[inline]
+pub fn try_index_of<I, const N: usize>(
+    array: [I; N],
+    bits: u32,
+) -> Result<<I as Interleave<N>>::Output, Error>
+where
+    I: Interleave<N> + BitCount + PrimInt,
+{
+    if bits > <I as BitCount>::BITS {
+        return Err(Error::BitsOutOfRange { bits });
+    }
+    if let Some(axis) = array
+        .iter()
+        .position(|&value| !util::fits_bits(value, bits))
+    {
+        return Err(Error::CoordinateOutOfRange { axis });
+    }
+
+    Ok(index_of(array))
+}
+
+/// Returns the N-dimensional coordinates of the given Z-order curve index, if
+/// the index is valid and each coordinate fits into `bits` bits.
+///
+/// An index is valid if it has no bits set above the interleaved
+/// coordinates, which is possible when the coordinates do not fill the index
+/// type, such as with 3 `u8` coordinates in a `u32` index. Coordinates fit as
+/// described in [`try_index_of`].
+///
+/// # Errors
+///
+/// Returns [`Error::BitsOutOfRange`] if `bits` is larger than the coordinate
+/// type, or [`Error::IndexOutOfRange`] if the index is not valid or any
+/// coordinate does not fit into `bits` bits.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{try_coord_of, Error};
+/// assert_eq!(try_coord_of(0b101_111u32, 2), Ok([3u8, 1u8, 3u8]));
+/// assert_eq!(try_coord_of::<_, 3>(1u32 << 24, 8), Err(Error::IndexOutOfRange));
+/// ```
+#[inline]
+pub fn try_coord_of<I, const N: usize>(
+    index: I,
+    bits: u32,
+) -> Result<[<I as Deinterleave<N>>::Output; N], Error>
+where
+    I: Deinterleave<N> + BitCount + PrimInt,
+{
+    if bits > <<I as Deinterleave<N>>::Output as BitCount>::BITS {
+        return Err(Error::BitsOutOfRange { bits });
+    }
+
+    if !util::is_valid_index::<I, N>(index) {
+        return Err(Error::IndexOutOfRange);
+    }
+
+    let coord = coord_of(index);
+    if !coord.iter().all(|&value| util::fits_bits(value, bits)) {
+        return Err(Error::IndexOutOfRange);
+    }
+
+    Ok(coord)
+}
+
+/// Calculates Z-order curve index for each coordinate set in `coords` into
+/// the same position of `indexes`.
+///
+/// This is the software fallback of
+/// [`avx2::index_of_batch`](crate::avx2::index_of_batch).
+///
+/// # Panics
+///
+/// If `coords` and `indexes` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::index_of_batch;
+/// let mut indexes = [0u64; 2];
+/// index_of_batch(&[[3u32, 7u32], [1u32, 0u32]], &mut indexes);
+/// assert_eq!(indexes, [0b101_111u64, 0b1u64]);
+/// ```
+#[inline]
+pub fn index_of_batch<I, const N: usize>(
+    coords: &[[I; N]],
+    indexes: &mut [<I as Interleave<N>>::Output],
+) where
+    I: Interleave<N> + Copy,
+{
+    assert_eq!(coords.len(), indexes.len(), "batch lengths differ");
+    for (index, &coord) in indexes.iter_mut().zip(coords) {
+        *index = index_of(coord);
+    }
+}
+
+/// Calculates the N-dimensional coordinates of each index in `indexes` into
+/// the same position of `coords`.
+///
+/// This is the software fallback of
+/// [`avx2::coord_of_batch`](crate::avx2::coord_of_batch).
+///
+/// # Panics
+///
+/// If `indexes` and `coords` have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::coord_of_batch;
+/// let mut coords = [[0u32; 2]; 2];
+/// coord_of_batch(&[0b101_111u64, 0b1u64], &mut coords);
+/// assert_eq!(coords, [[3u32, 7u32], [1u32, 0u32]]);
+/// ```
+#[inline]
+pub fn coord_of_batch<I, const N: usize>(
+    indexes: &[I],
+    coords: &mut [[<I as Deinterleave<N>>::Output; N]],
+) where
+    I: Deinterleave<N> + Copy,
+{
+    assert_eq!(indexes.len(), coords.len(), "batch lengths differ");
+    for (coord, &index) in coords.iter_mut().zip(indexes) {
+        *coord = coord_of(index);
+    }
 }
 
 /// `bmi2` module provides Z-order curve index and coordinate calculations
@@ -134,6 +468,8 @@ where
 pub mod bmi2 {
     pub use crate::{deinterleave::DeinterleaveBMI2, interleave::InterleaveBMI2};
 
+    use num_traits::PrimInt;
+
     use crate::{Deinterleave, Interleave};
 
     /// Returns true if the CPU supports the bmi2 instruction set.
@@ -198,8 +534,8 @@ pub mod bmi2 {
 
     /// Calculates Z-order curve index for given sequence of coordinates.
     ///
-    /// Output type will be the smallest unsigned integer type that can hold all
-    /// of the given coordinates.
+    /// Output type will be the smallest integer type that can hold all of the
+    /// given coordinates, and it is signed for signed coordinates.
     ///
     /// This function requires the `bmi2` instruction set, but it can be
     /// faster than the software implementation.
@@ -300,13 +636,141 @@ pub mod bmi2 {
     where
         I: DeinterleaveBMI2<N> + Copy,
     {
-        crate::util::generic_coord_of(index, |idx, i| idx.deinterleave_bmi2(i))
+        index.deinterleave_all_bmi2()
+    }
+
+    /// Safe wrapper around [`axis_of_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn axis_of<I, const N: usize>(
+        index: I,
+        axis: usize,
+        _support_token: HardwareSupportToken,
+    ) -> <I as Deinterleave<N>>::Output
+    where
+        I: DeinterleaveBMI2<N>,
+    {
+        // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+        // the `bmi2` instruction set is supported by the CPU and that it is safe
+        // to call `axis_of_unchecked`.
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            axis_of_unchecked(index, axis)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            let _ = (index, axis);
+            unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+        }
+    }
+
+    /// Returns a single coordinate of the given Z-order curve index without
+    /// decoding the other coordinates.
+    ///
+    /// This function requires the bmi2 instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU.
+    ///
+    /// # Panics
+    ///
+    /// If `axis` is not less than `N`.
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn axis_of_unchecked<I, const N: usize>(
+        index: I,
+        axis: usize,
+    ) -> <I as Deinterleave<N>>::Output
+    where
+        I: DeinterleaveBMI2<N>,
+    {
+        assert!(axis < N, "axis {axis} out of range for {N} dimensions");
+        index.deinterleave_bmi2(axis)
+    }
+
+    /// Safe wrapper around [`with_axis_unchecked`] that requires a
+    /// [`HardwareSupportToken`] to guarantee that the bmi2 instruction set is
+    /// supported by the CPU.
+    #[inline]
+    pub fn with_axis<I, const N: usize>(
+        index: I,
+        axis: usize,
+        value: <I as Deinterleave<N>>::Output,
+        _support_token: HardwareSupportToken,
+    ) -> I
+    where
+        I: DeinterleaveBMI2<N> + PrimInt,
+        <I as Deinterleave<N>>::Output: InterleaveBMI2<N, Output = I>,
+    {
+        // SAFETY: Having an instance of `HardwareSupportToken` guarantees that
+        // the `bmi2` instruction set is supported by the CPU and that it is safe
+        // to call `with_axis_unchecked`.
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            with_axis_unchecked(index, axis, value)
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            let _ = (index, axis, value);
+            unreachable!("HardwareSupportToken cannot be created on non-x86_64 platforms")
+        }
+    }
+
+    /// Returns the given Z-order curve index with a single coordinate
+    /// replaced by `value`. The other coordinates are kept as they are.
+    ///
+    /// This function requires the bmi2 instruction set, but it can be
+    /// faster than the software implementation.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call only if the `bmi2` x86_64 feature is
+    /// supported by the CPU.
+    ///
+    /// # Panics
+    ///
+    /// If `axis` is not less than `N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{bmi2, index_of};
+    /// if bmi2::has_hardware_support() {
+    ///     let idx = index_of([3u16, 7u16, 5u16]);
+    ///     let idx = unsafe { bmi2::with_axis_unchecked::<_, 3>(idx, 0, 1u16) };
+    ///     assert_eq!(idx, index_of([1u16, 7u16, 5u16]));
+    /// }
+    /// ```
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    #[cfg(target_arch = "x86_64")]
+    pub unsafe fn with_axis_unchecked<I, const N: usize>(
+        index: I,
+        axis: usize,
+        value: <I as Deinterleave<N>>::Output,
+    ) -> I
+    where
+        I: DeinterleaveBMI2<N> + PrimInt,
+        <I as Deinterleave<N>>::Output: InterleaveBMI2<N, Output = I>,
+    {
+        crate::util::generic_with_axis(
+            index,
+            axis,
+            value,
+            |idx, i| idx.deinterleave_bmi2(i),
+            |x| x.interleave_bmi2(),
+        )
     }
 }
 
 mod util {
-    use crate::{Deinterleave, Interleave};
-    use num_traits::Zero;
+    use crate::{mask::BitCount, Interleave};
+    use num_traits::{PrimInt, Zero};
 
     #[inline]
     pub(super) fn generic_index_of<I, const N: usize>(
@@ -316,22 +780,86 @@ mod util {
     where
         I: Interleave<N>,
     {
-        array.into_iter().map(interleave).enumerate().fold(
+        let index = array.into_iter().map(interleave).enumerate().fold(
             <I as Interleave<N>>::Output::zero(),
             |acc, (i, interleaved)| acc | (interleaved << i),
-        )
+        );
+
+        <I as Interleave<N>>::finish(index)
     }
 
+    /// Returns true if `value` fits into a `bits` wide integer of the same
+    /// signedness.
     #[inline]
+    pub(super) fn fits_bits<T: BitCount + PrimInt>(value: T, bits: u32) -> bool {
+        if bits >= <T as BitCount>::BITS {
+            true
+        } else if bits == 0 {
+            value.is_zero()
+        } else if T::min_value() < T::zero() {
+            // Bits above the sign bit must all be equal to the sign bit.
+            let high = value.signed_shr(bits - 1);
+            high.is_zero() || high == !T::zero()
+        } else {
+            value.unsigned_shr(bits).is_zero()
+        }
+    }
+
+    /// Returns true if the index has no bits set above the interleaved
+    /// coordinates.
+    #[inline]
+    pub(super) fn is_valid_index<I, const N: usize>(index: I) -> bool
+    where
+        I: crate::Deinterleave<N> + BitCount + PrimInt,
+    {
+        let used_bits = N as u32 * <<I as crate::Deinterleave<N>>::Output as BitCount>::BITS;
+        used_bits >= <I as BitCount>::BITS || index.unsigned_shr(used_bits).is_zero()
+    }
+
+    /// Replaces the coordinate of `axis` by flipping the bits which differ
+    /// between the old and the new interleaved coordinate. Since
+    /// [`Interleave::finish`] only flips constant bits, this works for every
+    /// coordinate type.
+    #[inline]
+    pub(super) fn generic_with_axis<I, const N: usize>(
+        index: I,
+        axis: usize,
+        value: <I as crate::Deinterleave<N>>::Output,
+        deinterleave: impl Fn(I, usize) -> <I as crate::Deinterleave<N>>::Output,
+        interleave: impl Fn(<I as crate::Deinterleave<N>>::Output) -> I,
+    ) -> I
+    where
+        I: crate::Deinterleave<N> + PrimInt,
+    {
+        assert!(axis < N, "axis {axis} out of range for {N} dimensions");
+        let old = deinterleave(index, axis);
+        index ^ ((interleave(old) ^ interleave(value)) << axis)
+    }
+
+    #[inline]
+    #[cfg(feature = "lut")]
     pub(super) fn generic_coord_of<I, const N: usize>(
         index: I,
-        deinterleave: impl Fn(I, usize) -> <I as Deinterleave<N>>::Output,
-    ) -> [<I as Deinterleave<N>>::Output; N]
+        deinterleave: impl Fn(I, usize) -> <I as crate::Deinterleave<N>>::Output,
+    ) -> [<I as crate::Deinterleave<N>>::Output; N]
     where
-        I: Deinterleave<N> + Copy,
+        I: crate::Deinterleave<N> + Copy,
     {
         core::array::from_fn(|i| deinterleave(index, i))
     }
+
+    /// Returns a deterministic xorshift sequence of pseudorandom numbers for
+    /// tests.
+    #[cfg(test)]
+    pub(crate) fn random() -> impl Iterator<Item = u64> {
+        let mut seed = 0x9E37_79B9_7F4A_7C15_u64;
+        core::iter::from_fn(move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            Some(seed)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -399,4 +927,195 @@ mod tests {
             assert_eq!(index_of(array), i);
         }
     }
+
+    #[test]
+    fn bmi2_matches_software_128() {
+        let Some(support_token) = bmi2::HardwareSupportToken::new() else {
+            return;
+        };
+
+        for i in 0..10_000u128 {
+            let i = i.wrapping_mul(0x9E37_79B9_7F4A_7C15_0123_4567_89AB_CDEF);
+
+            let array: [u64; 2] = coord_of(i);
+            assert_eq!(bmi2::coord_of(i, support_token), array);
+            assert_eq!(bmi2::index_of(array, support_token), i);
+
+            let array: [u32; 3] = coord_of(i);
+            assert_eq!(bmi2::coord_of(i, support_token), array);
+            assert_eq!(bmi2::index_of(array, support_token), index_of(array));
+
+            let array: [u16; 7] = coord_of(i);
+            assert_eq!(bmi2::coord_of(i, support_token), array);
+            assert_eq!(bmi2::index_of(array, support_token), index_of(array));
+
+            let array: [u8; 13] = coord_of(i);
+            assert_eq!(bmi2::coord_of(i, support_token), array);
+            assert_eq!(bmi2::index_of(array, support_token), index_of(array));
+
+            let array: [u8; 16] = coord_of(i);
+            assert_eq!(bmi2::coord_of(i, support_token), array);
+            assert_eq!(bmi2::index_of(array, support_token), i);
+
+            let array: [i64; 2] = coord_of(i as i128);
+            assert_eq!(bmi2::coord_of(i as i128, support_token), array);
+            assert_eq!(bmi2::index_of(array, support_token), i as i128);
+        }
+    }
+
+    #[test]
+    fn replace_each_axis() {
+        for i in 0..1_000u64 {
+            let coord: [u16; 3] = coord_of(i);
+            for axis in 0..3 {
+                assert_eq!(axis_of::<_, 3>(i, axis), coord[axis]);
+
+                let mut replaced = coord;
+                replaced[axis] = u16::MAX - coord[axis];
+                assert_eq!(
+                    with_axis::<_, 3>(i, axis, replaced[axis]),
+                    index_of(replaced)
+                );
+            }
+        }
+
+        for i in -1_000..1_000i128 {
+            let coord: [i64; 2] = coord_of(i);
+            for axis in 0..2 {
+                assert_eq!(axis_of::<_, 2>(i, axis), coord[axis]);
+
+                let mut replaced = coord;
+                replaced[axis] = !coord[axis];
+                assert_eq!(
+                    with_axis::<_, 2>(i, axis, replaced[axis]),
+                    index_of(replaced)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn replace_each_axis_bmi2() {
+        let Some(support_token) = bmi2::HardwareSupportToken::new() else {
+            return;
+        };
+
+        for i in 0..1_000u128 {
+            let i = i.wrapping_mul(0x9E37_79B9_7F4A_7C15_0123_4567_89AB_CDEF);
+            for axis in 0..5 {
+                assert_eq!(
+                    bmi2::axis_of::<_, 5>(i, axis, support_token),
+                    axis_of::<_, 5>(i, axis)
+                );
+                assert_eq!(
+                    bmi2::with_axis::<_, 5>(i, axis, axis as u16, support_token),
+                    with_axis::<_, 5>(i, axis, axis as u16)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn axis_out_of_range() {
+        axis_of::<_, 2>(0u32, 2);
+    }
+
+    #[test]
+    fn widened_index_and_back() {
+        for x in 0..=u8::MAX {
+            for y in [0u8, 1, 0x55, u8::MAX] {
+                let idx = index_of_as::<u64, _, 2>([x, y]);
+                assert_eq!(idx, u64::from(index_of([x, y])));
+                assert_eq!(coord_of_as::<u8, _, 2>(idx), [x, y]);
+                assert_eq!(coord_of_as::<u8, _, 2>(u128::from(idx)), [x, y]);
+            }
+        }
+
+        for x in i8::MIN..=i8::MAX {
+            let a = [x, x / 2, 7i8];
+            let b = [7i8, x, x / 3];
+            let wide = index_of_as::<i128, _, 3>(a);
+            assert_eq!(coord_of_as::<i8, _, 3>(wide), a);
+            assert_eq!(wide.cmp(&index_of_as(b)), index_of(a).cmp(&index_of(b)));
+        }
+    }
+
+    #[test]
+    fn checked_conversions() {
+        for x in 0..16u16 {
+            for y in 0..16u16 {
+                let fits = x < 8 && y < 8;
+                let idx = try_index_of([x, y], 3);
+                assert_eq!(idx.is_ok(), fits);
+                if fits {
+                    assert_eq!(try_coord_of(idx.unwrap(), 3), Ok([x, y]));
+                } else {
+                    assert!(try_coord_of::<_, 2>(index_of([x, y]), 3).is_err());
+                }
+            }
+        }
+
+        assert_eq!(
+            try_index_of([0i8, -5i8, 4i8], 3),
+            Err(Error::CoordinateOutOfRange { axis: 1 })
+        );
+        assert_eq!(try_coord_of(index_of([-4i8, 3i8]), 3), Ok([-4i8, 3i8]));
+        assert_eq!(
+            try_coord_of::<_, 2>(index_of([-5i8, 3i8]), 3),
+            Err(Error::IndexOutOfRange)
+        );
+
+        assert_eq!(try_coord_of::<_, 3>(u32::MAX >> 8, 8), Ok([u8::MAX; 3]));
+        assert_eq!(
+            try_coord_of::<_, 3>(u32::MAX, 8),
+            Err(Error::IndexOutOfRange)
+        );
+
+        assert_eq!(
+            try_index_of([0u8; 2], 9),
+            Err(Error::BitsOutOfRange { bits: 9 })
+        );
+        assert_eq!(
+            try_coord_of::<_, 2>(0u16, 9),
+            Err(Error::BitsOutOfRange { bits: 9 })
+        );
+    }
+
+    #[test]
+    fn index_and_back_signed() {
+        for x in i8::MIN..=i8::MAX {
+            for y in i8::MIN..=i8::MAX {
+                assert_eq!(coord_of(index_of([x, y])), [x, y]);
+            }
+        }
+
+        for i in 0..10_000i64 {
+            let array: [i16; 3] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+
+        for i in -10_000..10_000i128 {
+            let array: [i64; 2] = coord_of(i);
+            assert_eq!(index_of(array), i);
+        }
+    }
+
+    #[test]
+    fn signed_order_matches_offset_unsigned() {
+        let offset = |c: i8| (c as u8) ^ 0x80;
+
+        for [x0, y0] in [[-128i8, -128i8], [-1, 0], [0, -1], [5, -7], [127, 127]] {
+            for [x1, y1] in [[-128i8, 127i8], [0, 0], [-1, -1], [-7, 5], [127, -128]] {
+                let signed = index_of([x0, y0]).cmp(&index_of([x1, y1]));
+                let unsigned =
+                    index_of([offset(x0), offset(y0)]).cmp(&index_of([offset(x1), offset(y1)]));
+                assert_eq!(signed, unsigned);
+            }
+        }
+
+        let a = index_of([-1i16, 0i16, 0i16]);
+        let b = index_of([0i16, -1i16, -1i16]);
+        assert!(a > b);
+    }
 }
@@ -0,0 +1,254 @@
+//! `lut` module provides Z-order curve index and coordinate calculations
+//! using lookup tables.
+//!
+//! Coordinates are interleaved a byte at a time with a table of 256 spread
+//! bytes per dimension, or a nibble at a time with more than 9 dimensions,
+//! and indexes are deinterleaved a byte at a time with a
+//! table of 256 compacted bytes per dimension and bit phase. Tables are built
+//! at compile time only for the dimensions in use and work in `no_std`. This
+//! can be faster than the software implementation on targets without the
+//! `bmi2` instruction set.
+//!
+//! The functions have the same signatures as [`index_of`](crate::index_of)
+//! and [`coord_of`](crate::coord_of), but they support only unsigned
+//! integers. This module requires the `lut` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::lut::{coord_of, index_of};
+//!
+//! let idx = index_of([3u32, 7u32]);
+//! assert_eq!(idx, 0b101_111u64);
+//!
+//! let coord = coord_of(idx);
+//! assert_eq!(coord, [3u32, 7u32]);
+//! ```
+
+use num_traits::{cast::AsPrimitive, PrimInt, Unsigned, Zero};
+
+use crate::{mask::BitCount, Deinterleave, Interleave};
+
+/// Calculates Z-order curve index for given sequence of coordinates using
+/// lookup tables.
+///
+/// Output type will be the smallest unsigned integer type that can hold all
+/// of the given coordinates.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::lut::index_of;
+/// let idx = index_of([3u32, 7u32]);
+/// assert_eq!(idx, 0b101_111u64);
+/// ```
+#[inline]
+pub fn index_of<I, const N: usize>(array: [I; N]) -> <I as Interleave<N>>::Output
+where
+    I: InterleaveLUT<N>,
+{
+    crate::util::generic_index_of(array, InterleaveLUT::interleave_lut)
+}
+
+/// Returns the N-dimensional coordinates of the given Z-order curve index
+/// using lookup tables.
+///
+/// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
+/// you may need to specify the number of dimensions `N` to disambiguate.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::lut::coord_of;
+/// let coord = coord_of(0b101_111u64);
+/// assert_eq!(coord, [3u32, 7u32]);
+/// ```
+#[inline]
+pub fn coord_of<I, const N: usize>(index: I) -> [<I as Deinterleave<N>>::Output; N]
+where
+    I: DeinterleaveLUT<N>,
+{
+    crate::util::generic_coord_of(index, DeinterleaveLUT::deinterleave_lut)
+}
+
+/// Interleaves the bits of the given number using lookup tables.
+pub trait InterleaveLUT<const N: usize>: Interleave<N> {
+    /// Interleaves the bits of the given number using lookup tables.
+    fn interleave_lut(self) -> <Self as Interleave<N>>::Output;
+}
+
+impl<T, const N: usize> InterleaveLUT<N> for T
+where
+    T: Interleave<N> + BitCount + PrimInt + Unsigned + AsPrimitive<usize>,
+    u64: AsPrimitive<<T as Interleave<N>>::Output>,
+{
+    #[inline]
+    fn interleave_lut(self) -> <Self as Interleave<N>>::Output {
+        let chunk_bits = Tables::<N>::CHUNK_BITS;
+        let chunk_mask = T::from(0xFF >> (8 - chunk_bits)).unwrap_or_else(T::zero);
+
+        let mut x = <Self as Interleave<N>>::Output::zero();
+        for chunk in 0..<T as BitCount>::BITS / chunk_bits {
+            let bits: usize = ((self >> (chunk * chunk_bits) as usize) & chunk_mask).as_();
+            let spread: <Self as Interleave<N>>::Output = Tables::<N>::SPREAD[bits].as_();
+            x = x | (spread << ((chunk * chunk_bits) as usize * N));
+        }
+
+        x
+    }
+}
+
+/// Deinterleave a single number from a set of interleaved numbers using
+/// lookup tables. Inverse of [`InterleaveLUT`].
+pub trait DeinterleaveLUT<const N: usize>: Deinterleave<N> + Copy {
+    /// Deinterleaves a number from a set of interleaved numbers starting from
+    /// the given least significant bit (`lsb`) index using lookup tables.
+    fn deinterleave_lut(self, lsb: usize) -> <Self as Deinterleave<N>>::Output;
+}
+
+impl<T, const N: usize> DeinterleaveLUT<N> for T
+where
+    T: Deinterleave<N> + BitCount + PrimInt + Unsigned + AsPrimitive<usize>,
+    u8: AsPrimitive<<T as Deinterleave<N>>::Output>,
+{
+    #[inline]
+    fn deinterleave_lut(self, lsb: usize) -> <Self as Deinterleave<N>>::Output {
+        let out_bits = <<Self as Deinterleave<N>>::Output as BitCount>::BITS as usize;
+        let index = self >> lsb;
+
+        let mut x = <Self as Deinterleave<N>>::Output::zero();
+        for byte_idx in 0..(out_bits * N).div_ceil(8) {
+            let start = byte_idx * 8;
+            let phase = (N - start % N) % N;
+            let first = (start + phase) / N;
+            if first >= out_bits {
+                break;
+            }
+
+            let byte: usize = ((index >> start) & T::from(0xFF).unwrap()).as_();
+            let bits: <Self as Deinterleave<N>>::Output = Tables::<N>::COMPACT[phase][byte].as_();
+            x = x | (bits << first);
+        }
+
+        x
+    }
+}
+
+/// Lookup tables of dimension `N`.
+struct Tables<const N: usize>;
+
+impl<const N: usize> Tables<N> {
+    /// Number of coordinate bits spread with one lookup. Spreading a full
+    /// byte does not fit into `u64` with more than 9 dimensions, so larger
+    /// dimensions spread nibbles.
+    const CHUNK_BITS: u32 = if N <= 9 { 8 } else { 4 };
+
+    /// Bits of each byte spread `N` bits apart.
+    const SPREAD: &'static [u64; 256] = &spread_table(N);
+
+    /// Every `N`th bit of each byte starting from the phase index, compacted
+    /// together.
+    const COMPACT: &'static [[u8; 256]; N] = &compact_tables::<N>();
+}
+
+/// Builds the table of bytes spread `dim` bits apart. Bits which do not fit
+/// into `u64` are dropped.
+const fn spread_table(dim: usize) -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut bit = 0;
+        while bit < 8 && bit * dim < 64 {
+            if byte >> bit & 1 == 1 {
+                table[byte] |= 1 << (bit * dim);
+            }
+            bit += 1;
+        }
+        byte += 1;
+    }
+    table
+}
+
+/// Builds the tables of every `N`th bit of bytes, one table for each phase
+/// of the first extracted bit.
+const fn compact_tables<const N: usize>() -> [[u8; 256]; N] {
+    let mut tables = [[0u8; 256]; N];
+    let mut phase = 0;
+    while phase < N {
+        let mut byte = 0;
+        while byte < 256 {
+            let mut bit = phase;
+            let mut out_bit = 0;
+            while bit < 8 {
+                if byte >> bit & 1 == 1 {
+                    tables[phase][byte] |= 1 << out_bit;
+                }
+                bit += N;
+                out_bit += 1;
+            }
+            byte += 1;
+        }
+        phase += 1;
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<I, const N: usize>(indexes: impl Iterator<Item = I>)
+    where
+        I: DeinterleaveLUT<N> + PartialEq + core::fmt::Debug,
+        <I as Deinterleave<N>>::Output: InterleaveLUT<N, Output = I> + PartialEq + core::fmt::Debug,
+    {
+        for index in indexes {
+            let coord = coord_of::<_, N>(index);
+            assert_eq!(coord, crate::coord_of::<_, N>(index));
+            assert_eq!(index_of(coord), crate::index_of(coord));
+        }
+    }
+
+    #[test]
+    fn matches_software() {
+        let mut random = crate::util::random();
+
+        check::<u16, 2>(random.by_ref().take(1000).map(|x| x as u16));
+        check::<u32, 2>(random.by_ref().take(1000).map(|x| x as u32));
+        check::<u32, 3>(random.by_ref().take(1000).map(|x| x as u32));
+        check::<u64, 3>(random.by_ref().take(1000));
+        check::<u64, 5>(random.by_ref().take(1000));
+        check::<u64, 7>(random.by_ref().take(1000));
+        check::<u128, 2>(
+            random
+                .by_ref()
+                .take(1000)
+                .map(|x| (x as u128) << 64 | x as u128),
+        );
+        check::<u128, 9>(
+            random
+                .by_ref()
+                .take(1000)
+                .map(|x| (x as u128) << 64 | x as u128),
+        );
+        check::<u128, 13>(
+            random
+                .by_ref()
+                .take(1000)
+                .map(|x| (x as u128) << 64 | x as u128),
+        );
+        check::<u128, 16>(
+            random
+                .by_ref()
+                .take(1000)
+                .map(|x| (x as u128) << 64 | x as u128),
+        );
+    }
+
+    #[test]
+    fn full_coordinates() {
+        assert_eq!(index_of([u8::MAX; 13]), crate::index_of([u8::MAX; 13]));
+        assert_eq!(index_of([u64::MAX, 0]), crate::index_of([u64::MAX, 0]));
+        assert_eq!(coord_of::<_, 3>(u32::MAX), [u8::MAX; 3]);
+    }
+}
@@ -19,9 +19,10 @@ macro_rules! impl_bit_count {
     };
 }
 
-// Implement [`BitCount`] for all unsigned integer types.
+// Implement [`BitCount`] for all integer types.
 impl_bit_count! {
-    u8, u16, u32, u64, u128
+    u8, u16, u32, u64, u128,
+    i8, i16, i32, i64, i128
 }
 
 /// Calculates the shift amount for the given interleave step and dimension.
@@ -40,19 +41,250 @@ pub(crate) const fn interleave_shift(dim: u32, i: u32) -> u32 {
 /// # Panics
 ///
 /// Panic behavior is the same as [`bit_mask`] for the `bits` parameter.
-#[inline]
+#[cfg(test)]
 pub(crate) fn interleave_mask<T: num_traits::PrimInt + BitCount>(dim: u32, bits: u32) -> T {
-    let mut acc = <T as num_traits::Zero>::zero();
-    let mask = bit_mask::<T>(bits);
+    let mask = const_interleave_mask(dim, bits, <T as BitCount>::BITS);
+    // Mask is truncated to the width of `T`, so it always fits.
+    T::from(mask).unwrap()
+}
+
+/// `const` version of [`interleave_mask`] for a type with `type_bits` bits.
+/// The mask is returned as `u128` and truncated to `type_bits`.
+pub(crate) const fn const_interleave_mask(dim: u32, bits: u32, type_bits: u32) -> u128 {
+    assert!(bits > 0 && bits <= type_bits && type_bits <= u128::BITS);
 
-    let ceil_div_dim = <T as BitCount>::BITS.div_ceil(dim);
+    let mask = u128::MAX >> (u128::BITS - bits);
+    let ceil_div_dim = type_bits.div_ceil(dim);
     let ceil_div_bits = ceil_div_dim.div_ceil(bits);
 
-    for i in 0..ceil_div_bits {
-        acc = acc | mask.unsigned_shl(i * dim * bits);
+    let mut acc = 0;
+    let mut i = 0;
+    while i < ceil_div_bits {
+        acc |= mask << (i * dim * bits);
+        i += 1;
+    }
+
+    acc & (u128::MAX >> (u128::BITS - type_bits))
+}
+
+/// Calculates the masks and shift amounts of all steps in interleaving a
+/// `L` steps wide (`2^L` bits) number into a type with `type_bits` bits, in
+/// the order they are applied.
+pub(crate) const fn interleave_steps<const L: usize>(dim: u32, type_bits: u32) -> [(u128, u32); L] {
+    let mut steps = [(0, 0); L];
+    let mut i = 0;
+    while i < L {
+        let step = (L - 1 - i) as u32;
+        steps[i] = (
+            const_interleave_mask(dim, 1 << step, type_bits),
+            interleave_shift(dim, step),
+        );
+        i += 1;
+    }
+    steps
+}
+
+/// Calculates the masks and shift amounts of all steps in deinterleaving a
+/// `L` steps wide (`2^L` bits) number from a type with `type_bits` bits, in
+/// the order they are applied.
+pub(crate) const fn deinterleave_steps<const L: usize>(
+    dim: u32,
+    type_bits: u32,
+) -> [(u128, u32); L] {
+    let mut steps = [(0, 0); L];
+    let mut i = 0;
+    while i < L {
+        let step = i as u32;
+        steps[i] = (
+            const_interleave_mask(dim, 1 << (step + 1), type_bits),
+            interleave_shift(dim, step),
+        );
+        i += 1;
     }
+    steps
+}
 
-    acc
+/// Truncates the masks of the steps computed with [`interleave_steps`] or
+/// [`deinterleave_steps`] to the given type in a `const` context.
+macro_rules! const_steps {
+    ($steps:ident, $dim:expr, $len_type:ty, $mask_type:ty) => {{
+        const LEN: usize = <$len_type>::BITS.trailing_zeros() as usize;
+        const WIDE: [(u128, u32); LEN] = $crate::mask::$steps::<LEN>($dim, <$mask_type>::BITS);
+
+        let mut steps = [(0 as $mask_type, 0u32); LEN];
+        let mut i = 0;
+        while i < LEN {
+            steps[i] = (WIDE[i].0 as $mask_type, WIDE[i].1);
+            i += 1;
+        }
+        steps
+    }};
+}
+
+pub(crate) use const_steps;
+
+/// Calculates the mask of the bits that belong to each of the `N` axes of an
+/// interleaved number in a type with `type_bits` bits.
+pub(crate) const fn axis_masks<const N: usize>(type_bits: u32) -> [u128; N] {
+    let mask = const_interleave_mask(N as u32, 1, type_bits);
+    let mut masks = [0; N];
+    let mut i = 0;
+    while i < N {
+        masks[i] = (mask << i) & (u128::MAX >> (u128::BITS - type_bits));
+        i += 1;
+    }
+    masks
+}
+
+/// Calculates the masks and shift amounts of the delta swaps which
+/// deinterleave all `dim` numbers of a `type_bits` wide type at once, and
+/// the number of swaps used.
+///
+/// With a power of two `dim`, the numbers fill the type and deinterleaving
+/// rotates the bits of each bit index: bit `k * dim + a` moves to
+/// `a * (type_bits / dim) + k`. Each swap exchanges two bits of the bit
+/// indexes. Other dimensions are transposed as [`padded_dim`] after
+/// [`spread_moves`]. No swaps are returned for two dimensions, which are
+/// deinterleaved as fast one number at a time.
+pub(crate) const fn transpose_swaps(dim: u32, type_bits: u32) -> ([(u128, u32); 7], usize) {
+    let dim = padded_dim(dim, type_bits);
+    let mut swaps = [(0, 0); 7];
+    if dim < 4 || !dim.is_power_of_two() {
+        return (swaps, 0);
+    }
+
+    let index_bits = type_bits.trailing_zeros() as usize;
+    let dim_bits = dim.trailing_zeros() as usize;
+    let len_bits = index_bits - dim_bits;
+
+    // `origin[i]` is the bit of the original bit index which is currently
+    // stored in bit `i` of the bit index.
+    let mut origin = [0; 7];
+    let mut i = 0;
+    while i < index_bits {
+        origin[i] = i;
+        i += 1;
+    }
+
+    let mut len = 0;
+    let mut i = 0;
+    while i < index_bits {
+        let target = if i < len_bits {
+            dim_bits + i
+        } else {
+            i - len_bits
+        };
+
+        let mut j = i;
+        while origin[j] != target {
+            j += 1;
+        }
+
+        if j != i {
+            let mut mask = 0;
+            let mut bit = 0;
+            while bit < type_bits {
+                if (bit >> i) & 1 == 1 && (bit >> j) & 1 == 0 {
+                    mask |= 1 << bit;
+                }
+                bit += 1;
+            }
+
+            swaps[len] = (mask, (1 << j) - (1 << i));
+            origin[j] = origin[i];
+            origin[i] = target;
+            len += 1;
+        }
+        i += 1;
+    }
+
+    (swaps, len)
+}
+
+/// Returns the dimension which the bits of a `dim` dimensional index in a
+/// `type_bits` wide type are transposed as.
+///
+/// Other dimensions than powers of two are padded to the next power of two
+/// if the numbers are 8 bits wide. Wider numbers are deinterleaved faster
+/// one at a time than padded and transposed.
+pub(crate) const fn padded_dim(dim: u32, type_bits: u32) -> u32 {
+    let padded = dim.next_power_of_two();
+    if dim >= 3 && type_bits / padded == u8::BITS {
+        padded
+    } else {
+        dim
+    }
+}
+
+/// Calculates the masks and shift amounts of the moves which spread the
+/// groups of `dim` bits of an index to groups of [`padded_dim`] bits, and
+/// the number of moves used.
+///
+/// Each move shifts the bits of `mask` left by the shift amount. Afterwards,
+/// the index is laid out as if it had `padded_dim` axes, so that
+/// [`transpose_swaps`] deinterleaves all numbers. No moves are returned if
+/// the dimension is not padded.
+pub(crate) const fn spread_moves(dim: u32, type_bits: u32) -> ([(u128, u32); 7], usize) {
+    let mut moves = [(0, 0); 7];
+    let padded = padded_dim(dim, type_bits);
+    if padded == dim {
+        return (moves, 0);
+    }
+
+    // Number of groups, which is the width of the deinterleaved numbers.
+    let groups = type_bits / padded;
+    let group_mask = u128::MAX >> (u128::BITS - dim);
+
+    // Blocks of `2 * half` groups are split in halves, and the upper half of
+    // each block moves up until its groups start at multiples of `padded`.
+    let mut len = 0;
+    let mut half = groups / 2;
+    while half > 0 {
+        let mut mask = 0;
+        let mut group = 0;
+        while group < groups {
+            let offset = group % (2 * half);
+            if offset >= half {
+                let pos = padded * (group - offset) + dim * offset;
+                mask |= group_mask << pos;
+            }
+            group += 1;
+        }
+
+        moves[len] = (mask, (padded - dim) * half);
+        len += 1;
+        half /= 2;
+    }
+
+    (moves, len)
+}
+
+/// Truncates the masks computed with [`transpose_swaps`] or [`spread_moves`]
+/// to the given type in a `const` context.
+macro_rules! const_swaps {
+    ($swaps:ident, $dim:expr, $mask_type:ty) => {{
+        const WIDE: ([(u128, u32); 7], usize) = $crate::mask::$swaps($dim, <$mask_type>::BITS);
+
+        let mut swaps = [(0 as $mask_type, 0u32); WIDE.1];
+        let mut i = 0;
+        while i < WIDE.1 {
+            swaps[i] = (WIDE.0[i].0 as $mask_type, WIDE.0[i].1);
+            i += 1;
+        }
+        swaps
+    }};
+}
+
+pub(crate) use const_swaps;
+
+/// Calculates the mask of the bits that belong to the first axis of an
+/// interleaved number with `dim` axes of `bits` wide numbers in a type with
+/// `type_bits` bits.
+///
+/// Unlike [`const_interleave_mask`], bits above the interleaved numbers are
+/// never set.
+pub(crate) const fn const_axis_mask(dim: u32, bits: u32, type_bits: u32) -> u128 {
+    const_interleave_mask(dim, 1, type_bits) & (u128::MAX >> (u128::BITS - dim * bits))
 }
 
 /// Set the `n` least significant bits of given type.
@@ -69,6 +301,7 @@ pub(crate) fn bit_mask<T: num_traits::PrimInt + BitCount>(bits: u32) -> T {
 }
 
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod tests {
     use super::*;
 
@@ -197,6 +430,29 @@ mod tests {
         assert_eq!(interleave_mask::<u32>(2, 1), 0x5555_5555);
     }
 
+    #[test]
+    fn steps_match_runtime_masks() {
+        let steps = interleave_steps::<3>(3, u32::BITS);
+        for (k, step) in steps.into_iter().enumerate() {
+            let i = 2 - k as u32;
+            let mask = interleave_mask::<u32>(3, 1 << i) as u128;
+            assert_eq!(step, (mask, interleave_shift(3, i)));
+        }
+
+        let steps = deinterleave_steps::<4>(2, u32::BITS);
+        for (i, step) in steps.into_iter().enumerate() {
+            let i = i as u32;
+            let mask = interleave_mask::<u32>(2, 1 << (i + 1)) as u128;
+            assert_eq!(step, (mask, interleave_shift(2, i)));
+        }
+    }
+
+    #[test]
+    fn axis_mask_unused_bits() {
+        assert_eq!(const_axis_mask(3, 8, u32::BITS), 0x0024_9249);
+        assert_eq!(const_axis_mask(2, 16, u32::BITS), 0x5555_5555);
+    }
+
     #[test]
     fn odd_large_dimension_mask() {
         assert_eq!(
@@ -0,0 +1,298 @@
+use core::{cmp::Ordering, fmt, hash, marker::PhantomData};
+
+use crate::{
+    bmi2::{self, DeinterleaveBMI2, HardwareSupportToken, InterleaveBMI2},
+    Deinterleave, Error, Interleave,
+};
+
+/// Z-order curve index of `N` coordinates of type `T`.
+///
+/// Unlike the raw indexes returned by [`index_of`](crate::index_of), the
+/// coordinate type and dimension are part of the type, so an index cannot be
+/// decoded with the wrong dimension and decoding needs no type annotations.
+///
+/// Indexes are ordered, compared and hashed by their raw value. [`Debug`]
+/// shows the decoded coordinates, while [`Display`](fmt::Display) and
+/// [`Binary`](fmt::Binary) format the raw value.
+///
+/// # Examples
+///
+/// ```
+/// use zorder::MortonIndex;
+///
+/// let idx = MortonIndex::new([3u32, 7u32]);
+/// assert_eq!(idx.into_raw(), 0b101_111u64);
+/// assert_eq!(idx.coord(), [3u32, 7u32]);
+///
+/// assert_eq!(format!("{idx:?}"), "MortonIndex([3, 7])");
+/// assert_eq!(format!("{idx}"), "47");
+/// assert_eq!(format!("{idx:#b}"), "0b101111");
+/// ```
+pub struct MortonIndex<T, const N: usize>
+where
+    T: Interleave<N>,
+{
+    index: <T as Interleave<N>>::Output,
+    coord: PhantomData<[T; N]>,
+}
+
+impl<T, const N: usize> MortonIndex<T, N>
+where
+    T: Interleave<N>,
+{
+    /// Calculates the index of the given coordinates.
+    #[inline]
+    pub fn new(coord: [T; N]) -> Self {
+        Self::from_raw(crate::index_of(coord))
+    }
+
+    /// Calculates the index of the given coordinates using the bmi2
+    /// instruction set.
+    #[inline]
+    pub fn new_bmi2(coord: [T; N], support_token: HardwareSupportToken) -> Self
+    where
+        T: InterleaveBMI2<N>,
+    {
+        Self::from_raw(bmi2::index_of(coord, support_token))
+    }
+
+    /// Wraps an index returned by [`index_of`](crate::index_of) or one of its
+    /// variants for the same coordinate type and dimension.
+    ///
+    /// The index is not validated; use [`TryFrom`] for indexes from
+    /// untrusted sources.
+    #[inline]
+    pub const fn from_raw(index: <T as Interleave<N>>::Output) -> Self {
+        Self {
+            index,
+            coord: PhantomData,
+        }
+    }
+
+    /// Returns the raw index.
+    #[inline]
+    pub const fn into_raw(self) -> <T as Interleave<N>>::Output {
+        self.index
+    }
+
+    /// Returns the coordinates of the index.
+    #[inline]
+    pub fn coord(self) -> [T; N]
+    where
+        <T as Interleave<N>>::Output: Deinterleave<N, Output = T>,
+    {
+        crate::coord_of(self.index)
+    }
+
+    /// Returns the coordinates of the index using the bmi2 instruction set.
+    #[inline]
+    pub fn coord_bmi2(self, support_token: HardwareSupportToken) -> [T; N]
+    where
+        <T as Interleave<N>>::Output: DeinterleaveBMI2<N> + Deinterleave<N, Output = T>,
+    {
+        bmi2::coord_of(self.index, support_token)
+    }
+
+    /// Returns a single coordinate of the index without decoding the other
+    /// coordinates.
+    ///
+    /// # Panics
+    ///
+    /// If `axis` is not less than `N`.
+    #[inline]
+    pub fn axis(self, axis: usize) -> T
+    where
+        <T as Interleave<N>>::Output: Deinterleave<N, Output = T>,
+    {
+        crate::axis_of(self.index, axis)
+    }
+
+    /// Returns the index with a single coordinate replaced by `value`.
+    ///
+    /// # Panics
+    ///
+    /// If `axis` is not less than `N`.
+    #[inline]
+    pub fn with_axis(self, axis: usize, value: T) -> Self
+    where
+        <T as Interleave<N>>::Output: Deinterleave<N, Output = T>,
+    {
+        Self::from_raw(crate::with_axis(self.index, axis, value))
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+{
+    #[inline]
+    fn from(coord: [T; N]) -> Self {
+        Self::new(coord)
+    }
+}
+
+macro_rules! impl_try_from_raw {
+    ($($t:ty),*) => {
+        $(
+            impl<T, const N: usize> TryFrom<$t> for MortonIndex<T, N>
+            where
+                T: Interleave<N, Output = $t>,
+                $t: Deinterleave<N>,
+            {
+                type Error = Error;
+
+                /// Wraps a raw index, if it has no bits set above the
+                /// interleaved coordinates.
+                #[inline]
+                fn try_from(index: $t) -> Result<Self, Self::Error> {
+                    if crate::util::is_valid_index::<_, N>(index) {
+                        Ok(Self::from_raw(index))
+                    } else {
+                        Err(Error::IndexOutOfRange)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_raw!(u16, u32, u64, u128, i16, i32, i64, i128);
+
+impl<T, const N: usize> Clone for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const N: usize> Copy for MortonIndex<T, N> where T: Interleave<N> {}
+
+impl<T, const N: usize> PartialEq for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T, const N: usize> Eq for MortonIndex<T, N> where T: Interleave<N> {}
+
+impl<T, const N: usize> PartialOrd for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, const N: usize> Ord for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T, const N: usize> hash::Hash for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+    <T as Interleave<N>>::Output: hash::Hash,
+{
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for MortonIndex<T, N>
+where
+    T: Interleave<N> + fmt::Debug,
+    <T as Interleave<N>>::Output: Deinterleave<N, Output = T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MortonIndex").field(&self.coord()).finish()
+    }
+}
+
+impl<T, const N: usize> fmt::Display for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+    <T as Interleave<N>>::Output: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.index, f)
+    }
+}
+
+impl<T, const N: usize> fmt::Binary for MortonIndex<T, N>
+where
+    T: Interleave<N>,
+    <T as Interleave<N>>::Output: fmt::Binary,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&self.index, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_back() {
+        for i in 0..10_000u64 {
+            let coord: [u16; 3] = crate::coord_of(i);
+            let idx = MortonIndex::new(coord);
+            assert_eq!(idx.into_raw(), i);
+            assert_eq!(idx.coord(), coord);
+            assert_eq!(idx.axis(2), coord[2]);
+        }
+
+        let idx = MortonIndex::new([-3i32, 7i32]);
+        assert_eq!(idx.coord(), [-3i32, 7i32]);
+        assert_eq!(idx.with_axis(0, 5).coord(), [5i32, 7i32]);
+    }
+
+    #[test]
+    fn bmi2_matches_software() {
+        let Some(support_token) = HardwareSupportToken::new() else {
+            return;
+        };
+
+        let idx = MortonIndex::new_bmi2([3u64, 7u64], support_token);
+        assert_eq!(idx, MortonIndex::new([3u64, 7u64]));
+        assert_eq!(idx.coord_bmi2(support_token), [3u64, 7u64]);
+    }
+
+    #[test]
+    fn try_from_raw() {
+        assert_eq!(
+            MortonIndex::<u8, 3>::try_from(0xFF_FFFFu32),
+            Ok(MortonIndex::new([u8::MAX; 3]))
+        );
+        assert_eq!(
+            MortonIndex::<u8, 3>::try_from(0x100_0000u32),
+            Err(Error::IndexOutOfRange)
+        );
+        assert!(MortonIndex::<i16, 2>::try_from(-1i32).is_ok());
+        assert!(MortonIndex::<u16, 3>::try_from(u64::MAX >> 16).is_ok());
+        assert!(MortonIndex::<u16, 3>::try_from(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn ordered_by_index() {
+        let a = MortonIndex::from([1u8, 0u8]);
+        let b = MortonIndex::from([0u8, 1u8]);
+        assert!(a < b);
+        assert_eq!(a.cmp(&b), a.into_raw().cmp(&b.into_raw()));
+    }
+}
@@ -0,0 +1,356 @@
+//! Neighbor finding on Z-order curve indexes.
+//!
+//! Neighbors are computed directly from the interleaved bits using the
+//! arithmetic from [`dilated`](crate::dilated), without decoding the index.
+//! What happens at the edges of the coordinate space is chosen with
+//! [`Boundary`]. Only the unsigned indexes of unsigned coordinates are
+//! supported, since the edges are found from the raw bits of each axis.
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::{
+//!     index_of,
+//!     neighbors::{self, Boundary},
+//! };
+//!
+//! let idx = index_of([0u8, 5u8]);
+//!
+//! let faces: Vec<Option<u16>> = neighbors::face_neighbors::<_, 2>(idx, Boundary::Exclude).collect();
+//! assert_eq!(
+//!     faces,
+//!     [
+//!         None,
+//!         Some(index_of([1u8, 5u8])),
+//!         Some(index_of([0u8, 4u8])),
+//!         Some(index_of([0u8, 6u8])),
+//!     ]
+//! );
+//! ```
+
+use core::iter::FusedIterator;
+
+use num_traits::{PrimInt, Unsigned, WrappingAdd, WrappingSub};
+
+use crate::{dilated, mask::BitCount, Deinterleave};
+
+/// Behavior when a neighbor would be outside of the coordinate space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Boundary {
+    /// There is no neighbor past the edge.
+    Exclude,
+    /// The coordinate stays at the edge, so the neighbor may be the index
+    /// itself.
+    Saturate,
+    /// The coordinate wraps around to the opposite edge, as in a toroidal
+    /// space.
+    Wrap,
+}
+
+/// Direction of a step along an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Towards smaller coordinates.
+    Negative,
+    /// Towards larger coordinates.
+    Positive,
+}
+
+/// Returns the neighbor of `index` one step away along `axis`.
+///
+/// Returns `None` only if the neighbor is past the edge and `boundary` is
+/// [`Boundary::Exclude`].
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{index_of, neighbors::{self, Boundary, Direction}};
+/// let idx = index_of([7u16, u16::MAX]);
+///
+/// let up = neighbors::neighbor::<_, 2>(idx, 1, Direction::Positive, Boundary::Exclude);
+/// assert_eq!(up, None);
+///
+/// let up = neighbors::neighbor::<_, 2>(idx, 1, Direction::Positive, Boundary::Saturate);
+/// assert_eq!(up, Some(idx));
+///
+/// let up = neighbors::neighbor::<_, 2>(idx, 1, Direction::Positive, Boundary::Wrap);
+/// assert_eq!(up, Some(index_of([7u16, 0u16])));
+/// ```
+///
+/// ```compile_fail
+/// # use zorder::{index_of, neighbors::{self, Boundary, Direction}};
+/// let idx = index_of([0i8, 127i8]);
+/// let up = neighbors::neighbor::<_, 2>(idx, 1, Direction::Positive, Boundary::Exclude);
+/// ```
+#[inline]
+pub fn neighbor<I, const N: usize>(
+    index: I,
+    axis: usize,
+    direction: Direction,
+    boundary: Boundary,
+) -> Option<I>
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub,
+{
+    let mask = dilated::mask::<I, N>(axis);
+    let at_edge = match direction {
+        Direction::Negative => index & mask == I::zero(),
+        Direction::Positive => index & mask == mask,
+    };
+
+    match (at_edge, boundary) {
+        (true, Boundary::Exclude) => None,
+        (true, Boundary::Saturate) => Some(index),
+        (_, _) => Some(match direction {
+            Direction::Negative => dilated::decrement::<_, N>(index, axis),
+            Direction::Positive => dilated::increment::<_, N>(index, axis),
+        }),
+    }
+}
+
+/// Returns an iterator over the `2 * N` neighbors of `index` which share a
+/// face with it.
+///
+/// The neighbors are ordered by axis, and the negative direction comes
+/// before the positive direction. Neighbors past the edge are `None` when
+/// `boundary` is [`Boundary::Exclude`].
+#[inline]
+pub fn face_neighbors<I, const N: usize>(index: I, boundary: Boundary) -> FaceNeighbors<I, N>
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub,
+{
+    FaceNeighbors {
+        index,
+        boundary,
+        next: 0,
+    }
+}
+
+/// Returns an iterator over the `3^N - 1` neighbors of `index` which share
+/// a face, an edge or a corner with it, also known as the Moore
+/// neighborhood.
+///
+/// Each neighbor is reached by stepping -1, 0 or +1 along every axis. The
+/// neighbors are ordered by these steps, with the step of the last axis being
+/// the most significant. Neighbors past the edge are `None` when `boundary`
+/// is [`Boundary::Exclude`].
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::{index_of, neighbors::{self, Boundary}};
+/// let idx = index_of([0u8, 0u8]);
+///
+/// let moore: Vec<Option<u16>> = neighbors::moore_neighbors::<_, 2>(idx, Boundary::Wrap).collect();
+/// assert_eq!(moore.len(), 8);
+/// assert_eq!(moore[0], Some(index_of([u8::MAX, u8::MAX])));
+/// assert_eq!(moore[7], Some(index_of([1u8, 1u8])));
+/// ```
+#[inline]
+pub fn moore_neighbors<I, const N: usize>(index: I, boundary: Boundary) -> MooreNeighbors<I, N>
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub,
+{
+    MooreNeighbors {
+        index,
+        boundary,
+        steps: [0; N],
+        remaining: 3usize.pow(N as u32) - 1,
+    }
+}
+
+/// Iterator over the face neighbors of an index.
+///
+/// Created by [`face_neighbors`].
+#[derive(Debug, Clone)]
+pub struct FaceNeighbors<I, const N: usize> {
+    index: I,
+    boundary: Boundary,
+    next: usize,
+}
+
+impl<I, const N: usize> Iterator for FaceNeighbors<I, N>
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub,
+{
+    type Item = Option<I>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= 2 * N {
+            return None;
+        }
+
+        let axis = self.next / 2;
+        let direction = if self.next.is_multiple_of(2) {
+            Direction::Negative
+        } else {
+            Direction::Positive
+        };
+        self.next += 1;
+
+        Some(neighbor::<_, N>(self.index, axis, direction, self.boundary))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = 2 * N - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<I, const N: usize> ExactSizeIterator for FaceNeighbors<I, N> where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub
+{
+}
+
+impl<I, const N: usize> FusedIterator for FaceNeighbors<I, N> where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub
+{
+}
+
+/// Iterator over the Moore neighborhood of an index.
+///
+/// Created by [`moore_neighbors`].
+#[derive(Debug, Clone)]
+pub struct MooreNeighbors<I, const N: usize> {
+    index: I,
+    boundary: Boundary,
+    /// Step of each axis, where 0, 1 and 2 mean -1, 0 and +1.
+    steps: [u8; N],
+    remaining: usize,
+}
+
+impl<I, const N: usize> MooreNeighbors<I, N> {
+    /// Advances the steps as a base-3 counter, skipping the index itself.
+    #[inline]
+    fn advance(&mut self) {
+        loop {
+            for step in self.steps.iter_mut() {
+                *step += 1;
+                if *step < 3 {
+                    break;
+                }
+                *step = 0;
+            }
+
+            if self.steps.iter().any(|&step| step != 1) {
+                break;
+            }
+        }
+    }
+}
+
+impl<I, const N: usize> Iterator for MooreNeighbors<I, N>
+where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub,
+{
+    type Item = Option<I>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let neighbor = self
+            .steps
+            .iter()
+            .enumerate()
+            .try_fold(self.index, |index, (axis, step)| match step {
+                0 => neighbor::<_, N>(index, axis, Direction::Negative, self.boundary),
+                2 => neighbor::<_, N>(index, axis, Direction::Positive, self.boundary),
+                _ => Some(index),
+            });
+
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            self.advance();
+        }
+
+        Some(neighbor)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I, const N: usize> ExactSizeIterator for MooreNeighbors<I, N> where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub
+{
+}
+
+impl<I, const N: usize> FusedIterator for MooreNeighbors<I, N> where
+    I: Deinterleave<N> + BitCount + PrimInt + Unsigned + WrappingAdd + WrappingSub
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coord_of, index_of};
+
+    /// Moves the coordinate by `step` in `-1..=1` using the given boundary.
+    fn step_coord(value: u8, step: i8, boundary: Boundary) -> Option<u8> {
+        match (value.checked_add_signed(step), boundary) {
+            (Some(value), _) => Some(value),
+            (None, Boundary::Exclude) => None,
+            (None, Boundary::Saturate) => Some(value),
+            (None, Boundary::Wrap) => Some(value.wrapping_add_signed(step)),
+        }
+    }
+
+    #[test]
+    fn face_neighbors_match_coordinates() {
+        for boundary in [Boundary::Exclude, Boundary::Saturate, Boundary::Wrap] {
+            for idx in (0..=u32::MAX >> 8).step_by(4099) {
+                let coord: [u8; 3] = coord_of(idx);
+
+                let expected = (0..3).flat_map(|axis| {
+                    [-1, 1].map(|step| {
+                        let mut coord = coord;
+                        coord[axis] = step_coord(coord[axis], step, boundary)?;
+                        Some(index_of(coord))
+                    })
+                });
+
+                assert!(face_neighbors::<_, 3>(idx, boundary).eq(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn moore_neighbors_match_coordinates() {
+        for boundary in [Boundary::Exclude, Boundary::Saturate, Boundary::Wrap] {
+            for coord in [[0u8, 0u8], [5, 255], [255, 255], [17, 200]] {
+                let idx = index_of(coord);
+                let mut neighbors = moore_neighbors::<_, 2>(idx, boundary);
+                assert_eq!(neighbors.len(), 8);
+
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy) == (0, 0) {
+                            continue;
+                        }
+
+                        let expected = step_coord(coord[0], dx, boundary).and_then(|x| {
+                            step_coord(coord[1], dy, boundary).map(|y| index_of([x, y]))
+                        });
+                        assert_eq!(neighbors.next(), Some(expected));
+                    }
+                }
+                assert_eq!(neighbors.next(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn moore_neighborhood_size() {
+        let idx = index_of([1u8; 5]);
+
+        assert_eq!(moore_neighbors::<_, 5>(idx, Boundary::Exclude).len(), 242);
+        assert!(moore_neighbors::<_, 5>(idx, Boundary::Exclude).all(|n| n.is_some()));
+    }
+}
@@ -0,0 +1,183 @@
+use core::cmp::Ordering;
+
+use num_traits::{PrimInt, Unsigned};
+
+/// Compares two coordinates in Z-order without calculating their indexes.
+///
+/// The result is the same as comparing the indexes returned by
+/// [`index_of`](crate::index_of), but it works for any number of dimensions
+/// and coordinate widths, including combinations such as `[u64; 3]` which
+/// do not fit into any index type.
+///
+/// The axis with the most significant differing bit decides the order. If
+/// several axes differ at the same bit, the last of them decides, since its
+/// bit is more significant in the index.
+///
+/// # Examples
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use zorder::{cmp_zorder, index_of};
+/// let a = [3u32, 7u32];
+/// let b = [7u32, 3u32];
+/// assert_eq!(cmp_zorder(&a, &b), index_of(a).cmp(&index_of(b)));
+///
+/// let a = [u64::MAX, 0, 0];
+/// let b = [0, 0, 1];
+/// assert_eq!(cmp_zorder(&a, &b), Ordering::Greater);
+/// ```
+#[inline]
+pub fn cmp_zorder<T, const N: usize>(a: &[T; N], b: &[T; N]) -> Ordering
+where
+    T: PrimInt + Unsigned,
+{
+    if N == 0 {
+        return Ordering::Equal;
+    }
+
+    let mut axis = 0;
+    let mut diff = T::zero();
+
+    for (i, (&x, &y)) in a.iter().zip(b).enumerate() {
+        let d = x ^ y;
+        if !less_msb(d, diff) {
+            axis = i;
+            diff = d;
+        }
+    }
+
+    a[axis].cmp(&b[axis])
+}
+
+/// Returns true if the most significant set bit of `x` is less significant
+/// than the most significant set bit of `y`.
+#[inline]
+fn less_msb<T: PrimInt>(x: T, y: T) -> bool {
+    x < y && x < (x ^ y)
+}
+
+/// Coordinates which are ordered in Z-order.
+///
+/// Comparisons use [`cmp_zorder`], so the coordinates can be sorted or used
+/// as [`BTreeMap`](https://doc.rust-lang.org/std/collections/struct.BTreeMap.html)
+/// keys without calculating their indexes.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use zorder::ZOrdered;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert(ZOrdered([1u64, 1u64, 0u64]), "c");
+/// map.insert(ZOrdered([0u64, 1u64, 0u64]), "b");
+/// map.insert(ZOrdered([1u64, 0u64, 0u64]), "a");
+///
+/// let values: Vec<_> = map.into_values().collect();
+/// assert_eq!(values, ["a", "b", "c"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ZOrdered<C>(pub C);
+
+impl<C> ZOrdered<C> {
+    /// Returns the wrapped coordinates.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.0
+    }
+}
+
+impl<C> From<C> for ZOrdered<C> {
+    #[inline]
+    fn from(coord: C) -> Self {
+        Self(coord)
+    }
+}
+
+impl<T, const N: usize> PartialOrd for ZOrdered<[T; N]>
+where
+    T: PrimInt + Unsigned,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, const N: usize> Ord for ZOrdered<[T; N]>
+where
+    T: PrimInt + Unsigned,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_zorder(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index_of;
+
+    /// Compares the interleaved bits one at a time from the most
+    /// significant bit.
+    fn naive_cmp<T, const N: usize>(a: &[T; N], b: &[T; N]) -> Ordering
+    where
+        T: PrimInt + Unsigned,
+    {
+        let bits = T::zero().count_zeros() as usize;
+        for bit in (0..bits).rev() {
+            for axis in (0..N).rev() {
+                let x = (a[axis] >> bit) & T::one();
+                let y = (b[axis] >> bit) & T::one();
+                if x != y {
+                    return x.cmp(&y);
+                }
+            }
+        }
+        Ordering::Equal
+    }
+
+    #[test]
+    fn matches_index_order() {
+        for a in 0..64u8 {
+            for b in 0..64u8 {
+                let a = [a & 7, a >> 3, a.wrapping_mul(37)];
+                let b = [b & 7, b >> 3, b.wrapping_mul(37)];
+                assert_eq!(cmp_zorder(&a, &b), index_of(a).cmp(&index_of(b)));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_naive_wide() {
+        let mut random = crate::util::random();
+
+        for _ in 0..1000 {
+            let mut next = || random.next().unwrap() >> (random.next().unwrap() % 64);
+            let a = [next(), next(), next()];
+            let b = [next(), a[1], next()];
+            assert_eq!(cmp_zorder(&a, &b), naive_cmp(&a, &b));
+
+            let a = a.map(|x| (x as u128) << 64 | x as u128);
+            let b = b.map(|x| x as u128);
+            assert_eq!(cmp_zorder(&a, &b), naive_cmp(&a, &b));
+        }
+    }
+
+    #[test]
+    fn zero_dimensions() {
+        assert_eq!(cmp_zorder::<u8, 0>(&[], &[]), Ordering::Equal);
+    }
+
+    #[test]
+    fn sort_wrapped() {
+        let mut coords = [[1u64, 1u64], [0, 1], [1, 0], [0, 0]].map(ZOrdered);
+        coords.sort();
+        assert_eq!(
+            coords.map(ZOrdered::into_inner),
+            [[0, 0], [1, 0], [0, 1], [1, 1]]
+        );
+    }
+}
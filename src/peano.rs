@@ -0,0 +1,294 @@
+//! This module provides functions to convert N-dimensional coordinates to
+//! [Peano curve](https://en.wikipedia.org/wiki/Peano_curve) indexes and back.
+//!
+//! The Peano curve divides space into 3 parts along each axis on every
+//! level, which matches grids refined by a factor of 3. Coordinates are
+//! limited to `0..3^k`, where `3^k` is the largest power of 3 for which all
+//! coordinates fit into the coordinate type and all indexes fit into the
+//! output type. See [`side_len`] for the exact limit. Conversions outside of
+//! the limit return an [`Error`].
+//!
+//! Output types are the same as with [`index_of`](crate::index_of).
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::peano::{coord_of, index_of};
+//!
+//! let idx = index_of([1u8, 1u8]).unwrap();
+//! assert_eq!(idx, 4u16);
+//!
+//! let coord = coord_of(idx).unwrap();
+//! assert_eq!(coord, [1u8, 1u8]);
+//!
+//! assert!(index_of([243u8, 0u8]).is_err());
+//! ```
+
+use num_traits::{Bounded, NumCast, PrimInt, ToPrimitive};
+
+use crate::{Deinterleave, Error, Interleave};
+
+/// Returns the length of each side of the space covered by the Peano curve
+/// for the given coordinate type `I` and dimension `N`. Every coordinate must
+/// be less than this.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::peano::side_len;
+/// // 3^5 = 243 is the largest power of 3 not greater than 256.
+/// assert_eq!(side_len::<u8, 2>(), 243);
+/// // Three `u16` coordinates are stored in a `u64`, which holds `3^(3 * 10)`
+/// // indexes.
+/// assert_eq!(side_len::<u16, 3>(), 3u128.pow(10));
+/// ```
+#[inline]
+pub fn side_len<I, const N: usize>() -> u128
+where
+    I: Interleave<N> + PrimInt,
+{
+    3u128.pow(levels::<I, N>())
+}
+
+/// Returns the number of base 3 digits in each coordinate.
+#[inline]
+fn levels<I, const N: usize>() -> u32
+where
+    I: Interleave<N> + PrimInt,
+{
+    let max_coord = I::max_value().to_u128().unwrap_or(u128::MAX);
+    let max_index = <<I as Interleave<N>>::Output as Bounded>::max_value()
+        .to_u128()
+        .unwrap_or(u128::MAX);
+
+    let fits = |levels: u32| {
+        let coord_fits = 3u128
+            .checked_pow(levels)
+            .is_some_and(|side| side - 1 <= max_coord);
+        let index_fits = 3u128
+            .checked_pow(N as u32 * levels)
+            .is_some_and(|len| len - 1 <= max_index);
+        coord_fits && index_fits
+    };
+
+    let mut levels = 0;
+    while fits(levels + 1) {
+        levels += 1;
+    }
+    levels
+}
+
+/// Calculates Peano curve index for given sequence of coordinates.
+///
+/// Output type will be the smallest integer type that can hold all of the
+/// given coordinates. Coordinates must be non-negative and less than
+/// [`side_len`].
+///
+/// # Errors
+///
+/// Returns [`Error::CoordinateOutOfRange`] if any coordinate is negative or
+/// not less than [`side_len`].
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::peano::index_of;
+/// let idx = index_of([2u8, 0u8]).unwrap();
+/// assert_eq!(idx, 6u16);
+///
+/// assert!(index_of([-1i8, 0i8]).is_err());
+/// ```
+#[inline]
+pub fn index_of<I, const N: usize>(array: [I; N]) -> Result<<I as Interleave<N>>::Output, Error>
+where
+    I: Interleave<N> + PrimInt,
+{
+    let levels = levels::<I, N>();
+    let side = 3u128.pow(levels);
+
+    let mut coord = [0u128; N];
+    for (axis, (value, coord)) in array.iter().zip(coord.iter_mut()).enumerate() {
+        *coord = value
+            .to_u128()
+            .filter(|&value| value < side)
+            .ok_or(Error::CoordinateOutOfRange { axis })?;
+    }
+
+    let mut index = 0u128;
+    let mut digit_parity = Parity::<N>::new();
+
+    for level in (0..levels).rev() {
+        let place = 3u128.pow(level);
+
+        for (axis, value) in coord.iter().enumerate() {
+            let digit = (value / place % 3) as u8;
+            let digit = digit_parity.orient(axis, digit);
+            digit_parity.push(axis, digit);
+
+            index = index * 3 + digit as u128;
+        }
+    }
+
+    // Index is always less than `3^(N * levels)` which fits into the output.
+    Ok(<<I as Interleave<N>>::Output as NumCast>::from(index).unwrap())
+}
+
+/// Returns the N-dimensional coordinates of the given Peano curve index.
+///
+/// Since many coordinate type and dimension combinations can be mapped to the same type `I`,
+/// you may need to specify the number of dimensions `N` to disambiguate.
+///
+/// # Errors
+///
+/// Returns [`Error::IndexOutOfRange`] if the index is not less than
+/// [`side_len`] to the power of `N`.
+///
+/// # Examples
+///
+/// ```
+/// # use zorder::peano::coord_of;
+/// let coord = coord_of(6u16).unwrap();
+/// assert_eq!(coord, [2u8, 0u8]);
+///
+/// assert!(coord_of::<_, 2>(u16::MAX).is_err());
+/// ```
+#[inline]
+pub fn coord_of<I, const N: usize>(index: I) -> Result<[<I as Deinterleave<N>>::Output; N], Error>
+where
+    I: Deinterleave<N> + PrimInt,
+    <I as Deinterleave<N>>::Output: Interleave<N, Output = I>,
+{
+    let levels = levels::<<I as Deinterleave<N>>::Output, N>();
+    let digits = N as u32 * levels;
+
+    let index = index
+        .to_u128()
+        .filter(|&index| index < 3u128.pow(digits))
+        .ok_or(Error::IndexOutOfRange)?;
+
+    let mut coord = [0u128; N];
+    let mut digit_parity = Parity::<N>::new();
+
+    for digit_index in 0..digits {
+        let axis = digit_index as usize % N;
+        let digit = (index / 3u128.pow(digits - 1 - digit_index) % 3) as u8;
+
+        coord[axis] = coord[axis] * 3 + digit_parity.orient(axis, digit) as u128;
+        digit_parity.push(axis, digit);
+    }
+
+    // Coordinates are always less than `3^levels` which fits into the output.
+    Ok(coord.map(|value| NumCast::from(value).unwrap()))
+}
+
+/// Tracks the parity of the sum of index digits of each axis.
+///
+/// A coordinate digit is reflected if the sum of the previous index digits
+/// of all other axes is odd. Pushing a digit of an axis doesn't change the
+/// orientation of that axis.
+struct Parity<const N: usize> {
+    total: u8,
+    axes: [u8; N],
+}
+
+impl<const N: usize> Parity<N> {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            total: 0,
+            axes: [0; N],
+        }
+    }
+
+    /// Maps a digit between coordinate and index digit. The mapping is its
+    /// own inverse.
+    #[inline]
+    fn orient(&self, axis: usize, digit: u8) -> u8 {
+        if (self.total ^ self.axes[axis]) & 1 == 1 {
+            2 - digit
+        } else {
+            digit
+        }
+    }
+
+    /// Adds an index digit of the given axis to the sums.
+    #[inline]
+    fn push(&mut self, axis: usize, digit: u8) {
+        self.total ^= digit & 1;
+        self.axes[axis] ^= digit & 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns true if the coordinates differ by one along exactly one axis.
+    fn adjacent<const N: usize>(a: [u8; N], b: [u8; N]) -> bool {
+        let diff: u32 = a
+            .iter()
+            .zip(b.iter())
+            .map(|(&a, &b)| a.abs_diff(b) as u32)
+            .sum();
+        diff == 1
+    }
+
+    #[test]
+    fn side_lengths() {
+        assert_eq!(side_len::<u8, 2>(), 243);
+        assert_eq!(side_len::<u8, 3>(), 243);
+        assert_eq!(side_len::<u16, 2>(), 3u128.pow(10));
+        assert_eq!(side_len::<u64, 2>(), 3u128.pow(40));
+        assert_eq!(side_len::<u8, 16>(), 3u128.pow(5));
+    }
+
+    #[test]
+    fn continuous_2d() {
+        let side = side_len::<u8, 2>();
+        let mut prev: [u8; 2] = coord_of(0u16).unwrap();
+        assert_eq!(prev, [0, 0]);
+
+        for i in 1..(side * side) as u16 {
+            let coord = coord_of(i).unwrap();
+            assert!(adjacent(prev, coord), "{prev:?} -> {coord:?} at {i}");
+            assert_eq!(index_of(coord), Ok(i));
+            prev = coord;
+        }
+
+        assert_eq!(prev, [242, 242]);
+    }
+
+    #[test]
+    fn continuous_3d() {
+        let mut prev: [u8; 3] = coord_of(0u32).unwrap();
+
+        for i in 1..3u32.pow(9) {
+            let coord = coord_of(i).unwrap();
+            assert!(adjacent(prev, coord), "{prev:?} -> {coord:?} at {i}");
+            assert_eq!(index_of(coord), Ok(i));
+            prev = coord;
+        }
+    }
+
+    #[test]
+    fn index_and_back_128() {
+        let max = 3u128.pow(80) - 1;
+        for i in (0..10_000u128).chain(max - 10_000..=max) {
+            let coord: [u64; 2] = coord_of(i).unwrap();
+            assert_eq!(index_of(coord), Ok(i));
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(
+            index_of([0u8, 243u8]),
+            Err(Error::CoordinateOutOfRange { axis: 1 })
+        );
+        assert_eq!(coord_of::<_, 2>(59049u16), Err(Error::IndexOutOfRange));
+        assert_eq!(
+            index_of([u64::MAX, 0u64]),
+            Err(Error::CoordinateOutOfRange { axis: 0 })
+        );
+    }
+}
@@ -0,0 +1,1033 @@
+//! Box queries over Z-order curve indexes.
+//!
+//! Data sorted by Z-order index can be searched for all points inside an
+//! axis-aligned box without decoding every index. [`BoundingBox`] provides
+//! the `BIGMIN` and `LITMAX` primitives described by Tropf and Herzog in
+//! _Multidimensional Range Search in Dynamically Balanced Trees_ (1981),
+//! which compute the next and previous index inside the box directly from
+//! the interleaved bits. They, and [`BoundingBox::indexes`] which is built on
+//! them, are specific to the Z-order curve.
+//!
+//! For storage which is scanned by index ranges, [`BoundingBox::ranges`]
+//! decomposes the box into the contiguous index ranges it covers, and
+//! [`BoundingBox::ranges_with_budget`] limits the number of ranges by merging
+//! ranges which are close to each other. Both work with any
+//! [`SpaceFillingCurve`] through [`BoundingBox::curve_ranges`] and
+//! [`BoundingBox::curve_ranges_with_budget`], and the indexes of any curve
+//! inside the box are iterated with [`BoundingBox::curve_indexes`].
+//!
+//! # Examples
+//!
+//! ```
+//! use zorder::{index_of, query::BoundingBox};
+//!
+//! let bbox = BoundingBox::new([1u8, 1u8], [2u8, 2u8]);
+//!
+//! // Index 4 is [2, 0], which is outside of the box.
+//! let idx = bbox.bigmin(4u16);
+//! assert_eq!(idx, Some(index_of([2u8, 1u8])));
+//! ```
+
+use core::iter::FusedIterator;
+
+use num_traits::{CheckedAdd, CheckedSub, One, PrimInt, Unsigned, Zero};
+
+use crate::{
+    curve::{checked_pow_minus_one, SpaceFillingCurve, ZOrder},
+    mask::BitCount,
+    Deinterleave, Interleave,
+};
+
+/// Axis-aligned box of N-dimensional coordinates. Both corners are inclusive.
+///
+/// Only unsigned coordinates are supported, since the queries work on the
+/// raw bits of the coordinates.
+///
+/// ```compile_fail
+/// # use zorder::query::BoundingBox;
+/// let bbox = BoundingBox::new([-3i8, -3i8], [3i8, 3i8]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox<I, const N: usize> {
+    min: [I; N],
+    max: [I; N],
+}
+
+impl<I, const N: usize> BoundingBox<I, N>
+where
+    I: Interleave<N> + PrimInt + Unsigned,
+{
+    /// Creates a new box from two opposite corners.
+    ///
+    /// The corners don't need to be ordered; minimum and maximum are chosen
+    /// separately for each axis.
+    #[inline]
+    pub fn new(a: [I; N], b: [I; N]) -> Self {
+        Self {
+            min: core::array::from_fn(|i| a[i].min(b[i])),
+            max: core::array::from_fn(|i| a[i].max(b[i])),
+        }
+    }
+
+    /// Returns the corner with the smallest coordinates.
+    #[inline]
+    pub fn min(&self) -> [I; N] {
+        self.min
+    }
+
+    /// Returns the corner with the largest coordinates.
+    #[inline]
+    pub fn max(&self) -> [I; N] {
+        self.max
+    }
+
+    /// Returns true if the given coordinates are inside the box.
+    #[inline]
+    pub fn contains(&self, coord: &[I; N]) -> bool {
+        (0..N).all(|i| self.min[i] <= coord[i] && coord[i] <= self.max[i])
+    }
+}
+
+impl<I, const N: usize> BoundingBox<I, N>
+where
+    I: Interleave<N> + PrimInt + Unsigned,
+    <I as Interleave<N>>::Output: Deinterleave<N>,
+{
+    /// Returns true if the coordinates of the given Z-order index are inside
+    /// the box. The index is not decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{index_of, query::BoundingBox};
+    /// let bbox = BoundingBox::new([1u8, 1u8], [2u8, 2u8]);
+    ///
+    /// assert!(bbox.contains_index(index_of([2u8, 1u8])));
+    /// assert!(!bbox.contains_index(index_of([3u8, 1u8])));
+    /// ```
+    #[inline]
+    pub fn contains_index(&self, index: <I as Interleave<N>>::Output) -> bool {
+        let (min, max) = self.index_bounds();
+        contains::<_, N>(index, min, max)
+    }
+
+    /// Returns the smallest Z-order index which is greater than `index` and
+    /// whose coordinates are inside the box, also known as `BIGMIN`.
+    ///
+    /// Returns `None` if there is no such index. The search works on the
+    /// interleaved bits of the index, so other curves are not supported; use
+    /// [`curve_ranges`](Self::curve_ranges) to skip over their indexes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{index_of, query::BoundingBox};
+    /// let bbox = BoundingBox::new([1u8, 1u8], [2u8, 2u8]);
+    ///
+    /// assert_eq!(bbox.bigmin(0u16), Some(index_of([1u8, 1u8])));
+    /// assert_eq!(bbox.bigmin(index_of([2u8, 2u8])), None);
+    /// ```
+    #[inline]
+    pub fn bigmin(
+        &self,
+        index: <I as Interleave<N>>::Output,
+    ) -> Option<<I as Interleave<N>>::Output> {
+        let (min, max) = self.index_bounds();
+        let next = index.checked_add(&One::one())?;
+        next_in_box::<_, N>(next, min, max)
+    }
+
+    /// Returns the largest Z-order index which is less than `index` and
+    /// whose coordinates are inside the box, also known as `LITMAX`.
+    ///
+    /// Returns `None` if there is no such index. Like [`bigmin`](Self::bigmin),
+    /// this only supports the Z-order curve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{index_of, query::BoundingBox};
+    /// let bbox = BoundingBox::new([1u8, 1u8], [2u8, 2u8]);
+    ///
+    /// assert_eq!(bbox.litmax(u16::MAX), Some(index_of([2u8, 2u8])));
+    /// assert_eq!(bbox.litmax(index_of([1u8, 1u8])), None);
+    /// ```
+    #[inline]
+    pub fn litmax(
+        &self,
+        index: <I as Interleave<N>>::Output,
+    ) -> Option<<I as Interleave<N>>::Output> {
+        let (min, max) = self.index_bounds();
+        let prev = index.checked_sub(&One::one())?;
+        prev_in_box::<_, N>(prev, min, max)
+    }
+
+    /// Returns an iterator over all Z-order indexes inside the box in
+    /// ascending order.
+    ///
+    /// Indexes of other curves are iterated with
+    /// [`curve_indexes`](Self::curve_indexes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{index_of, query::BoundingBox};
+    /// let bbox = BoundingBox::new([1u8, 1u8], [2u8, 2u8]);
+    ///
+    /// let indexes: Vec<u16> = bbox.indexes().collect();
+    /// assert_eq!(
+    ///     indexes,
+    ///     [
+    ///         index_of([1u8, 1u8]),
+    ///         index_of([2u8, 1u8]),
+    ///         index_of([1u8, 2u8]),
+    ///         index_of([2u8, 2u8]),
+    ///     ]
+    /// );
+    /// ```
+    #[inline]
+    pub fn indexes(&self) -> Indexes<<I as Interleave<N>>::Output, N> {
+        let (min, max) = self.index_bounds();
+        Indexes {
+            next: Some(min),
+            min,
+            max,
+        }
+    }
+
+    #[inline]
+    fn index_bounds(&self) -> (<I as Interleave<N>>::Output, <I as Interleave<N>>::Output) {
+        (crate::index_of(self.min), crate::index_of(self.max))
+    }
+}
+
+impl<I, const N: usize> BoundingBox<I, N>
+where
+    I: Interleave<N> + PrimInt + Unsigned,
+    <I as Interleave<N>>::Output: Deinterleave<N, Output = I>,
+{
+    /// Returns an iterator over the contiguous Z-order index ranges which
+    /// together cover exactly the box.
+    ///
+    /// Ranges are returned in ascending order and neighboring ranges are
+    /// always separated by at least one index outside of the box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::query::{BoundingBox, IndexRange};
+    /// let bbox = BoundingBox::new([0u8, 0u8], [1u8, 3u8]);
+    ///
+    /// let ranges: Vec<IndexRange<u16>> = bbox.ranges().collect();
+    /// assert_eq!(
+    ///     ranges,
+    ///     [
+    ///         IndexRange { start: 0, end: 3, fully_inside: true },
+    ///         IndexRange { start: 8, end: 11, fully_inside: true },
+    ///     ]
+    /// );
+    /// ```
+    #[inline]
+    pub fn ranges(&self) -> Ranges<I, N> {
+        self.curve_ranges(ZOrder)
+    }
+
+    /// Decomposes the box into at most `buf.len()` Z-order index ranges.
+    ///
+    /// Exact ranges are produced as with [`ranges`](Self::ranges). Whenever
+    /// there are more ranges than fit into `buf`, the two neighboring ranges
+    /// with the smallest gap between them are merged. Merged ranges contain
+    /// indexes outside of the box and have `fully_inside` set to false.
+    ///
+    /// Returns the used part of `buf`.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::query::{BoundingBox, IndexRange};
+    /// let bbox = BoundingBox::new([0u8, 0u8], [1u8, 3u8]);
+    ///
+    /// let mut buf = [IndexRange::default(); 1];
+    /// let ranges = bbox.ranges_with_budget(&mut buf);
+    /// assert_eq!(ranges, [IndexRange { start: 0u16, end: 11, fully_inside: false }]);
+    /// ```
+    #[inline]
+    pub fn ranges_with_budget<'a>(
+        &self,
+        buf: &'a mut [IndexRange<<I as Interleave<N>>::Output>],
+    ) -> &'a mut [IndexRange<<I as Interleave<N>>::Output>] {
+        self.curve_ranges_with_budget(ZOrder, buf)
+    }
+}
+
+impl<I, const N: usize> BoundingBox<I, N>
+where
+    I: PrimInt + Unsigned,
+{
+    /// Returns an iterator over the contiguous index ranges of the given
+    /// curve which together cover exactly the box.
+    ///
+    /// Ranges are returned in ascending order and neighboring ranges are
+    /// always separated by at least one index outside of the box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{curve::Hilbert, query::{BoundingBox, IndexRange}};
+    /// let bbox = BoundingBox::new([0u8, 0u8], [1u8, 3u8]);
+    ///
+    /// let ranges: Vec<IndexRange<u16>> = bbox.curve_ranges(Hilbert).collect();
+    /// assert_eq!(
+    ///     ranges,
+    ///     [IndexRange { start: 0, end: 7, fully_inside: true }]
+    /// );
+    /// ```
+    #[inline]
+    pub fn curve_ranges<C>(&self, curve: C) -> Ranges<I, N, C>
+    where
+        C: SpaceFillingCurve<I, N>,
+        C::Index: PrimInt,
+    {
+        Ranges {
+            bbox: *self,
+            curve,
+            root: true,
+            stack: [Frame {
+                next: None,
+                level: 0,
+                remaining: Zero::zero(),
+            }; STACK_SIZE],
+            depth: 0,
+            pending: None,
+        }
+    }
+
+    /// Returns an iterator over all indexes of the given curve inside the box
+    /// in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zorder::{curve::Hilbert, query::BoundingBox};
+    /// let bbox = BoundingBox::new([1u8, 0u8], [1u8, 1u8]);
+    ///
+    /// let indexes: Vec<u16> = bbox.curve_indexes(Hilbert).collect();
+    /// assert_eq!(indexes, [1, 2]);
+    /// ```
+    #[inline]
+    pub fn curve_indexes<C>(&self, curve: C) -> CurveIndexes<I, N, C>
+    where
+        C: SpaceFillingCurve<I, N>,
+        C::Index: PrimInt,
+    {
+        CurveIndexes {
+            ranges: self.curve_ranges(curve),
+            range: None,
+        }
+    }
+
+    /// Decomposes the box into at most `buf.len()` index ranges of the given
+    /// curve.
+    ///
+    /// Works like [`ranges_with_budget`](Self::ranges_with_budget), but with
+    /// the ranges of [`curve_ranges`](Self::curve_ranges).
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is empty.
+    pub fn curve_ranges_with_budget<'a, C>(
+        &self,
+        curve: C,
+        buf: &'a mut [IndexRange<C::Index>],
+    ) -> &'a mut [IndexRange<C::Index>]
+    where
+        C: SpaceFillingCurve<I, N>,
+        C::Index: PrimInt,
+    {
+        assert!(!buf.is_empty(), "range budget must be at least one");
+
+        let mut len = 0;
+        for range in self.curve_ranges(curve) {
+            if len < buf.len() {
+                buf[len] = range;
+                len += 1;
+                continue;
+            }
+
+            // Gap to the new range is the last candidate, so that ties merge
+            // the earlier ranges first.
+            let (merge_at, _) = buf[..len]
+                .windows(2)
+                .map(|pair| pair[1].start - pair[0].end)
+                .chain(core::iter::once(range.start - buf[len - 1].end))
+                .enumerate()
+                .fold((0, None), |(best, best_gap), (i, gap)| match best_gap {
+                    Some(best_gap) if best_gap <= gap => (best, Some(best_gap)),
+                    _ => (i, Some(gap)),
+                });
+
+            if merge_at + 1 < len {
+                buf[merge_at] = buf[merge_at].merge(buf[merge_at + 1]);
+                buf.copy_within(merge_at + 2..len, merge_at + 1);
+                buf[len - 1] = range;
+            } else {
+                buf[len - 1] = buf[len - 1].merge(range);
+            }
+        }
+
+        &mut buf[..len]
+    }
+}
+
+/// Contiguous range of curve indexes. Both ends are inclusive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IndexRange<K> {
+    /// First index of the range.
+    pub start: K,
+    /// Last index of the range.
+    pub end: K,
+    /// True if the coordinates of every index in the range are inside the
+    /// box, and false if the range only partially overlaps the box.
+    pub fully_inside: bool,
+}
+
+impl<K> IndexRange<K> {
+    #[inline]
+    fn merge(self, next: Self) -> Self {
+        Self {
+            start: self.start,
+            end: next.end,
+            fully_inside: false,
+        }
+    }
+}
+
+/// Maximum depth of the cell hierarchy: one level for each coordinate bit and
+/// the root.
+const STACK_SIZE: usize = u64::BITS as usize + 1;
+
+/// Cell whose children are being visited.
+#[derive(Clone, Copy)]
+struct Frame<K> {
+    /// Index of the next child cell, or `None` if all children were visited.
+    next: Option<K>,
+    /// Level of the child cells, where cells of level `l` are `RADIX^l` wide.
+    level: u32,
+    /// Number of child cells left to visit after `next`.
+    remaining: K,
+}
+
+/// Iterator over the curve index ranges covering a [`BoundingBox`].
+///
+/// Created by [`BoundingBox::ranges`] and [`BoundingBox::curve_ranges`].
+/// Ranges are found by descending only into the cells which partially
+/// overlap the box, and no memory is allocated.
+#[derive(Clone)]
+pub struct Ranges<I, const N: usize, C = ZOrder>
+where
+    C: SpaceFillingCurve<I, N>,
+{
+    bbox: BoundingBox<I, N>,
+    curve: C,
+    root: bool,
+    stack: [Frame<C::Index>; STACK_SIZE],
+    depth: usize,
+    pending: Option<IndexRange<C::Index>>,
+}
+
+/// Position of a cell relative to the box.
+enum Overlap {
+    Outside,
+    Partial,
+    Inside,
+}
+
+impl<I, const N: usize, C> Ranges<I, N, C>
+where
+    I: PrimInt + Unsigned,
+    C: SpaceFillingCurve<I, N>,
+    C::Index: PrimInt,
+{
+    /// Returns the next cell inside the box as a range of indexes.
+    fn next_cell(&mut self) -> Option<IndexRange<C::Index>> {
+        if core::mem::take(&mut self.root) {
+            match self.visit(Zero::zero(), root_level::<I, N, C>()) {
+                Some(range) => return Some(range),
+                None if self.depth == 0 => return None,
+                None => {}
+            }
+        }
+
+        while self.depth > 0 {
+            let frame = &mut self.stack[self.depth - 1];
+            let Some(index) = frame.next else {
+                self.depth -= 1;
+                continue;
+            };
+
+            let level = frame.level;
+            if frame.remaining.is_zero() {
+                frame.next = None;
+            } else {
+                frame.remaining = frame.remaining - One::one();
+                frame.next = Some(index + cell_span::<_, N, C, I>(level) + One::one());
+            }
+
+            if let Some(range) = self.visit(index, level) {
+                return Some(range);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the cell as a range if it is inside the box. Partially
+    /// overlapping cells are pushed onto the stack.
+    fn visit(&mut self, index: C::Index, level: u32) -> Option<IndexRange<C::Index>> {
+        match self.overlap(index, level) {
+            Overlap::Outside => None,
+            Overlap::Inside => Some(IndexRange {
+                start: index,
+                end: index + cell_span::<_, N, C, I>(level),
+                fully_inside: true,
+            }),
+            Overlap::Partial => {
+                // Only cells above level zero overlap partially, and the root
+                // level keeps the `RADIX^N` children of such a cell countable
+                // in the index type, even when `u32` would overflow.
+                let children = checked_pow_minus_one::<C::Index>(C::RADIX, N as u32)
+                    .expect("child cells of a partially overlapping cell fit into the index type");
+                self.stack[self.depth] = Frame {
+                    next: Some(index),
+                    level: level - 1,
+                    remaining: children,
+                };
+                self.depth += 1;
+                None
+            }
+        }
+    }
+
+    /// Classifies the cell of given `level` starting at `index`.
+    fn overlap(&self, index: C::Index, level: u32) -> Overlap {
+        // The first index of a cell may be at any corner of the cell.
+        let coord = self.curve.coord_of(index);
+        let extent = checked_pow_minus_one::<I>(C::RADIX, level).unwrap_or_else(I::max_value);
+
+        let mut inside = true;
+        for ((value, min), max) in coord.into_iter().zip(self.bbox.min).zip(self.bbox.max) {
+            let lo = if extent == I::max_value() {
+                I::zero()
+            } else {
+                value - value % (extent + I::one())
+            };
+            let hi = lo + extent;
+            if hi < min || max < lo {
+                return Overlap::Outside;
+            }
+            inside &= min <= lo && hi <= max;
+        }
+
+        if inside {
+            Overlap::Inside
+        } else {
+            Overlap::Partial
+        }
+    }
+}
+
+impl<I, const N: usize, C> Iterator for Ranges<I, N, C>
+where
+    I: PrimInt + Unsigned,
+    C: SpaceFillingCurve<I, N>,
+    C::Index: PrimInt,
+{
+    type Item = IndexRange<C::Index>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(cell) = self.next_cell() {
+            match &mut self.pending {
+                Some(pending) if pending.end + One::one() == cell.start => pending.end = cell.end,
+                pending => {
+                    if let Some(range) = pending.replace(cell) {
+                        return Some(range);
+                    }
+                }
+            }
+        }
+
+        self.pending.take()
+    }
+}
+
+impl<I, const N: usize, C> FusedIterator for Ranges<I, N, C>
+where
+    I: PrimInt + Unsigned,
+    C: SpaceFillingCurve<I, N>,
+    C::Index: PrimInt,
+{
+}
+
+/// Iterator over the curve indexes inside a [`BoundingBox`].
+///
+/// Created by [`BoundingBox::curve_indexes`]. Indexes are enumerated from the
+/// ranges of [`BoundingBox::curve_ranges`], so indexes outside of the box are
+/// never visited.
+#[derive(Clone)]
+pub struct CurveIndexes<I, const N: usize, C = ZOrder>
+where
+    C: SpaceFillingCurve<I, N>,
+{
+    ranges: Ranges<I, N, C>,
+    range: Option<IndexRange<C::Index>>,
+}
+
+impl<I, const N: usize, C> Iterator for CurveIndexes<I, N, C>
+where
+    I: PrimInt + Unsigned,
+    C: SpaceFillingCurve<I, N>,
+    C::Index: PrimInt,
+{
+    type Item = C::Index;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(range) = &mut self.range {
+                let index = range.start;
+                if range.start == range.end {
+                    self.range = None;
+                } else {
+                    range.start = range.start + One::one();
+                }
+                return Some(index);
+            }
+
+            self.range = Some(self.ranges.next()?);
+        }
+    }
+}
+
+impl<I, const N: usize, C> FusedIterator for CurveIndexes<I, N, C>
+where
+    I: PrimInt + Unsigned,
+    C: SpaceFillingCurve<I, N>,
+    C::Index: PrimInt,
+{
+}
+
+/// Returns the level of the cell covering the whole coordinate space: the
+/// largest level whose cells fit into both the coordinate and the index
+/// types.
+#[inline]
+fn root_level<I, const N: usize, C>() -> u32
+where
+    I: PrimInt + Unsigned,
+    C: SpaceFillingCurve<I, N>,
+    C::Index: PrimInt,
+{
+    let mut level = 0;
+    while checked_pow_minus_one::<I>(C::RADIX, level + 1).is_some()
+        && checked_pow_minus_one::<C::Index>(C::RADIX, N as u32 * (level + 1)).is_some()
+    {
+        level += 1;
+    }
+    level
+}
+
+/// Returns the difference between the last and the first index of a cell of
+/// given `level`.
+#[inline]
+fn cell_span<K, const N: usize, C, I>(level: u32) -> K
+where
+    K: PrimInt,
+    C: SpaceFillingCurve<I, N, Index = K>,
+{
+    checked_pow_minus_one::<K>(C::RADIX, N as u32 * level).unwrap_or_else(K::max_value)
+}
+
+/// Iterator over the Z-order indexes inside a [`BoundingBox`].
+///
+/// Created by [`BoundingBox::indexes`]. Indexes outside of the box are
+/// skipped using `BIGMIN`, so the cost of iteration depends only on the
+/// number of indexes inside the box.
+#[derive(Debug, Clone)]
+pub struct Indexes<K, const N: usize> {
+    next: Option<K>,
+    min: K,
+    max: K,
+}
+
+impl<K, const N: usize> Iterator for Indexes<K, N>
+where
+    K: Deinterleave<N> + BitCount + PrimInt,
+{
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.next?;
+
+        self.next = index.checked_add(&K::one()).and_then(|next| {
+            if contains::<_, N>(next, self.min, self.max) {
+                Some(next)
+            } else {
+                next_in_box::<_, N>(next, self.min, self.max)
+            }
+        });
+
+        Some(index)
+    }
+}
+
+impl<K, const N: usize> FusedIterator for Indexes<K, N> where K: Deinterleave<N> + BitCount + PrimInt
+{}
+
+/// Returns true if all of the axes of `index` are between the axes of `min`
+/// and `max`.
+#[inline]
+fn contains<K, const N: usize>(index: K, min: K, max: K) -> bool
+where
+    K: Deinterleave<N> + BitCount + PrimInt,
+{
+    let axis_mask = <K as Deinterleave<N>>::AXIS_MASK;
+
+    (0..N).all(|i| {
+        let mask = axis_mask << i;
+        let value = index & mask;
+        min & mask <= value && value <= max & mask
+    })
+}
+
+/// Sets `bit` and clears the lower bits of the same axis.
+#[inline]
+fn load_10<K: PrimInt>(value: K, bit: K, below: K) -> K {
+    (value & !below) | bit
+}
+
+/// Clears `bit` and sets the lower bits of the same axis.
+#[inline]
+fn load_01<K: PrimInt>(value: K, bit: K, below: K) -> K {
+    (value & !bit) | below
+}
+
+/// Returns the smallest index inside the box which is greater than or equal
+/// to `index`.
+fn next_in_box<K, const N: usize>(index: K, mut min: K, mut max: K) -> Option<K>
+where
+    K: Deinterleave<N> + BitCount + PrimInt,
+{
+    let axis_mask = <K as Deinterleave<N>>::AXIS_MASK;
+    let mut candidate = None;
+
+    for pos in (0..K::BITS as usize).rev() {
+        let bit = K::one() << pos;
+        let below = (axis_mask << (pos % N)) & (bit - K::one());
+
+        match (
+            index & bit != K::zero(),
+            min & bit != K::zero(),
+            max & bit != K::zero(),
+        ) {
+            (false, false, false) | (true, true, true) => {}
+            (false, false, true) => {
+                candidate = Some(load_10(min, bit, below));
+                max = load_01(max, bit, below);
+            }
+            (false, true, true) => return Some(min),
+            (true, false, false) => return candidate,
+            (true, false, true) => min = load_10(min, bit, below),
+            (_, true, false) => unreachable!("box minimum is greater than maximum"),
+        }
+    }
+
+    Some(index)
+}
+
+/// Returns the largest index inside the box which is less than or equal to
+/// `index`.
+fn prev_in_box<K, const N: usize>(index: K, mut min: K, mut max: K) -> Option<K>
+where
+    K: Deinterleave<N> + BitCount + PrimInt,
+{
+    let axis_mask = <K as Deinterleave<N>>::AXIS_MASK;
+    let mut candidate = None;
+
+    for pos in (0..K::BITS as usize).rev() {
+        let bit = K::one() << pos;
+        let below = (axis_mask << (pos % N)) & (bit - K::one());
+
+        match (
+            index & bit != K::zero(),
+            min & bit != K::zero(),
+            max & bit != K::zero(),
+        ) {
+            (false, false, false) | (true, true, true) => {}
+            (false, false, true) => max = load_01(max, bit, below),
+            (false, true, true) => return candidate,
+            (true, false, false) => return Some(max),
+            (true, false, true) => {
+                candidate = Some(load_01(max, bit, below));
+                min = load_10(min, bit, below);
+            }
+            (_, true, false) => unreachable!("box minimum is greater than maximum"),
+        }
+    }
+
+    Some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord_of;
+
+    #[test]
+    fn contains_index_matches_coordinates() {
+        let bbox = BoundingBox::new([3u8, 10u8], [17u8, 12u8]);
+
+        for idx in 0..=u16::MAX {
+            let coord: [u8; 2] = coord_of(idx);
+            assert_eq!(bbox.contains_index(idx), bbox.contains(&coord));
+        }
+    }
+
+    #[test]
+    fn unordered_corners() {
+        let bbox = BoundingBox::new([5u16, 1u16, 9u16], [2u16, 7u16, 3u16]);
+
+        assert_eq!(bbox.min(), [2, 1, 3]);
+        assert_eq!(bbox.max(), [5, 7, 9]);
+    }
+
+    #[test]
+    fn bigmin_and_litmax_brute_force_2d() {
+        let bbox = BoundingBox::new([3u8, 10u8], [17u8, 12u8]);
+
+        let mut bigmin = None;
+        for idx in (0..=u16::MAX).rev() {
+            assert_eq!(bbox.bigmin(idx), bigmin, "bigmin of {idx}");
+            if bbox.contains(&coord_of(idx)) {
+                bigmin = Some(idx);
+            }
+        }
+
+        let mut litmax = None;
+        for idx in 0..=u16::MAX {
+            assert_eq!(bbox.litmax(idx), litmax, "litmax of {idx}");
+            if bbox.contains(&coord_of(idx)) {
+                litmax = Some(idx);
+            }
+        }
+    }
+
+    #[test]
+    fn bigmin_unused_bits_3d() {
+        // `u32` holds only 24 bits of three `u8` coordinates.
+        let bbox = BoundingBox::new([0u8, 0u8, 0u8], [u8::MAX, u8::MAX, u8::MAX]);
+
+        assert_eq!(bbox.bigmin(0x00FF_FFFE), Some(0x00FF_FFFF));
+        assert_eq!(bbox.bigmin(0x00FF_FFFF), None);
+        assert_eq!(bbox.bigmin(0x0100_0000), None);
+        assert_eq!(bbox.litmax(u32::MAX), Some(0x00FF_FFFF));
+    }
+
+    #[test]
+    fn indexes_brute_force_3d() {
+        let bbox = BoundingBox::new([1u8, 4u8, 2u8], [6u8, 5u8, 9u8]);
+
+        let mut iter = bbox.indexes();
+        for idx in 0..(1u32 << 12) {
+            if bbox.contains(&coord_of(idx)) {
+                assert_eq!(iter.next(), Some(idx));
+            }
+        }
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn ranges_cover_box_exactly_2d() {
+        let bbox = BoundingBox::new([3u8, 10u8], [17u8, 12u8]);
+
+        let mut covered = 0;
+        let mut prev_end: Option<u16> = None;
+        for range in bbox.ranges() {
+            assert!(range.fully_inside);
+            assert!(range.start <= range.end);
+            if let Some(prev_end) = prev_end {
+                assert!(prev_end + 1 < range.start, "ranges must not touch");
+            }
+            for idx in range.start..=range.end {
+                assert!(bbox.contains(&coord_of(idx)));
+            }
+            covered += (range.end - range.start) as usize + 1;
+            prev_end = Some(range.end);
+        }
+
+        assert_eq!(covered, 15 * 3);
+    }
+
+    /// Peano curve limited to the coordinates it covers, which checks the
+    /// ranges of a curve with an odd radix.
+    #[derive(Clone, Copy)]
+    struct Peano;
+
+    impl SpaceFillingCurve<u8, 2> for Peano {
+        type Index = u16;
+
+        const RADIX: u32 = 3;
+
+        fn index_of(&self, coord: [u8; 2]) -> u16 {
+            crate::peano::index_of(coord).unwrap()
+        }
+
+        fn coord_of(&self, index: u16) -> [u8; 2] {
+            crate::peano::coord_of(index).unwrap()
+        }
+    }
+
+    #[test]
+    fn curve_ranges_cover_box_exactly_2d() {
+        fn check<C: SpaceFillingCurve<u8, 2, Index = u16> + Copy>(curve: C, side: u32) {
+            let bbox = BoundingBox::new([3u8, 10u8], [17u8, 12u8]);
+
+            let mut expected = 0..0;
+            let mut ranges = bbox.curve_ranges(curve);
+            for idx in (0..side * side).map(|idx| idx as u16) {
+                let inside = bbox.contains(&curve.coord_of(idx));
+                if inside && expected.is_empty() {
+                    expected = idx..idx + 1;
+                } else if inside {
+                    expected.end += 1;
+                } else if !expected.is_empty() {
+                    let range = ranges.next().unwrap();
+                    assert_eq!((range.start, range.end + 1), (expected.start, expected.end));
+                    expected = 0..0;
+                }
+            }
+            assert_eq!(ranges.next(), None);
+        }
+
+        check(ZOrder, 256);
+        check(crate::curve::Hilbert, 256);
+        check(crate::curve::Gray, 256);
+        check(Peano, 243);
+    }
+
+    #[test]
+    fn curve_indexes_brute_force_3d() {
+        let bbox = BoundingBox::new([1u8, 4u8, 2u8], [6u8, 5u8, 9u8]);
+        let curve = crate::curve::Hilbert;
+
+        let expected = (0..(1u32 << 12)).filter(|&idx| bbox.contains(&curve.coord_of(idx)));
+        assert!(bbox.curve_indexes(curve).eq(expected));
+    }
+
+    /// Z-order curve over base `2^16` digits, whose cells have more children
+    /// than fit into `u32`.
+    #[derive(Clone, Copy)]
+    struct WideDigits;
+
+    impl SpaceFillingCurve<u32, 2> for WideDigits {
+        type Index = u64;
+
+        const RADIX: u32 = 1 << 16;
+
+        fn index_of(&self, [x, y]: [u32; 2]) -> u64 {
+            let digits = |v: u32| (u64::from(v >> 16), u64::from(v & 0xFFFF));
+            let ((x_hi, x_lo), (y_hi, y_lo)) = (digits(x), digits(y));
+            (y_hi << 48) | (x_hi << 32) | (y_lo << 16) | x_lo
+        }
+
+        fn coord_of(&self, index: u64) -> [u32; 2] {
+            let digit = |shift: u32| ((index >> shift) & 0xFFFF) as u32;
+            [(digit(32) << 16) | digit(0), (digit(48) << 16) | digit(16)]
+        }
+    }
+
+    #[test]
+    fn curve_ranges_large_radix() {
+        let bbox = BoundingBox::new([0u32, 0u32], [1u32, 2u32]);
+
+        // Every child of the partially overlapping cells would be visited
+        // before the iterator ends, so only the first ranges are checked.
+        let mut ranges = bbox.curve_ranges(WideDigits);
+        for start in [0, 1 << 16] {
+            let range = IndexRange {
+                start,
+                end: start + 1,
+                fully_inside: true,
+            };
+            assert_eq!(ranges.next(), Some(range));
+        }
+    }
+
+    #[test]
+    fn ranges_whole_domain_3d() {
+        let bbox = BoundingBox::new([0u8, 0u8, 0u8], [u8::MAX, u8::MAX, u8::MAX]);
+
+        let mut ranges = bbox.ranges();
+        assert_eq!(
+            ranges.next(),
+            Some(IndexRange {
+                start: 0u32,
+                end: 0x00FF_FFFF,
+                fully_inside: true
+            })
+        );
+        assert_eq!(ranges.next(), None);
+    }
+
+    #[test]
+    fn ranges_single_point() {
+        let coord = [12345u16, 321u16, 7u16];
+        let bbox = BoundingBox::new(coord, coord);
+        let idx = crate::index_of(coord);
+
+        let mut ranges = bbox.ranges();
+        assert_eq!(
+            ranges.next(),
+            Some(IndexRange {
+                start: idx,
+                end: idx,
+                fully_inside: true
+            })
+        );
+        assert_eq!(ranges.next(), None);
+    }
+
+    #[test]
+    fn ranges_with_budget_merges_smallest_gaps() {
+        let bbox = BoundingBox::new([3u8, 10u8], [17u8, 12u8]);
+
+        let mut exact = [IndexRange::default(); 64];
+        let exact = bbox.ranges_with_budget(&mut exact);
+        assert!(exact.len() > 4);
+        assert!(exact.iter().all(|range| range.fully_inside));
+
+        let mut buf = [IndexRange::default(); 4];
+        let merged = bbox.ranges_with_budget(&mut buf);
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged[0].start, exact[0].start);
+        assert_eq!(merged[3].end, exact[exact.len() - 1].end);
+
+        // Every kept gap is at least as large as any gap that was merged.
+        let kept_gaps = merged.windows(2).map(|pair| pair[1].start - pair[0].end);
+        let min_kept_gap = kept_gaps.min().unwrap();
+        for pair in exact.windows(2) {
+            let gap = pair[1].start - pair[0].end;
+            let kept = merged.windows(2).any(|m| m[0].end == pair[0].end);
+            if !kept {
+                assert!(gap <= min_kept_gap);
+            }
+        }
+
+        for range in merged.iter() {
+            let all_inside = (range.start..=range.end).all(|idx| bbox.contains(&coord_of(idx)));
+            assert_eq!(range.fully_inside, all_inside);
+        }
+    }
+}
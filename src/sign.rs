@@ -0,0 +1,78 @@
+use num_traits::PrimInt;
+
+use crate::mask::BitCount;
+
+/// Reinterprets the bits of an integer as its same width counterpart of the
+/// opposite signedness.
+pub trait Reinterpret: BitCount + PrimInt {
+    /// Integer type of the same width but opposite signedness.
+    type Other: BitCount + PrimInt;
+
+    /// Reinterprets the bits of the number without changing them.
+    fn reinterpret(self) -> Self::Other;
+}
+
+macro_rules! impl_reinterpret {
+    ($($a:ty, $b:ty);*) => {
+        $(
+            impl Reinterpret for $a {
+                type Other = $b;
+
+                #[inline]
+                fn reinterpret(self) -> Self::Other {
+                    self as $b
+                }
+            }
+
+            impl Reinterpret for $b {
+                type Other = $a;
+
+                #[inline]
+                fn reinterpret(self) -> Self::Other {
+                    self as $a
+                }
+            }
+        )*
+    };
+}
+
+impl_reinterpret! {
+    u8, i8;
+    u16, i16;
+    u32, i32;
+    u64, i64;
+    u128, i128
+}
+
+/// Returns the number with only the most significant bit set.
+#[inline]
+pub(crate) fn sign_bit<T: BitCount + PrimInt>() -> T {
+    T::one().unsigned_shl(<T as BitCount>::BITS - 1)
+}
+
+/// Returns true if the coordinates of `dim` axes of type `C` fill every bit
+/// of the index type `I`, in which case the most significant bit of the index
+/// is the sign bit of the last axis.
+#[inline]
+pub(crate) fn fills_index<C: BitCount, I: BitCount>(dim: usize) -> bool {
+    dim as u32 * <C as BitCount>::BITS == <I as BitCount>::BITS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_bits() {
+        assert_eq!(sign_bit::<u8>(), 0x80);
+        assert_eq!(sign_bit::<u32>(), 0x8000_0000);
+        assert_eq!(sign_bit::<i16>(), i16::MIN);
+    }
+
+    #[test]
+    fn reinterpret_round_trip() {
+        assert_eq!((-1i32).reinterpret(), u32::MAX);
+        assert_eq!(u64::MAX.reinterpret(), -1i64);
+        assert_eq!(i8::MIN.reinterpret().reinterpret(), i8::MIN);
+    }
+}